@@ -1,4 +1,4 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use petgraph::{graph::NodeIndex, stable_graph::StableGraph};
 use serde_json::Value;
@@ -20,6 +20,43 @@ pub(crate) fn get_size_map(cargo_bloat_output: &str) -> HashMap<String, usize> {
     map
 }
 
+/// The workspace of the root crate, as parsed from the cargo metadata output.
+#[derive(Debug, Default)]
+pub(crate) struct Workspace {
+    /// Directory of the workspace root.
+    pub(crate) root: Option<String>,
+    /// Directories of the workspace members.
+    pub(crate) members: HashSet<String>,
+}
+
+/// Parse the cargo metadata output into the workspace root and member directories.
+pub(crate) fn get_workspace(cargo_metadata_output: &str) -> Workspace {
+    let json: Value = serde_json::from_str(cargo_metadata_output).unwrap();
+    let root = json["workspace_root"].as_str().unwrap().to_string();
+    let member_ids: HashSet<&str> = json["workspace_members"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_str().unwrap())
+        .collect();
+    let members = json["packages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|p| member_ids.contains(p["id"].as_str().unwrap()))
+        .filter_map(|p| {
+            let manifest_path = std::path::Path::new(p["manifest_path"].as_str().unwrap());
+            manifest_path
+                .parent()
+                .map(|dir| dir.to_string_lossy().into_owned())
+        })
+        .collect();
+    Workspace {
+        root: Some(root),
+        members,
+    }
+}
+
 /// Parse the cargo tree output into a dependency DAG.
 pub(crate) fn get_dep_graph(cargo_tree_output: &str) -> StableGraph<NodeWeight, EdgeWeight> {
     fn add_edge(
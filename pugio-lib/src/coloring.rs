@@ -1,3 +1,5 @@
+use std::collections::{HashMap, VecDeque};
+
 use serde::Deserialize;
 
 use crate::graph::Graph;
@@ -52,8 +54,35 @@ pub enum NodeColoringScheme {
     DepCount,
     /// Reverse dependency count
     ///
-    /// The number of paths from the root to a node.
+    /// The number of direct dependents of a node.
     RevDepCount,
+    /// Path count
+    ///
+    /// The number of paths from the root to a node, saturating at [`usize::MAX`].
+    PathCount,
+    /// Exclusive size
+    ///
+    /// The size of a node and all its dependencies that are only reachable through it, i.e. the
+    /// size that would be removed along with it.
+    Exclusive,
+    /// Self size
+    ///
+    /// The size of a node itself, excluding its dependencies.
+    SelfSize,
+    /// Depth
+    ///
+    /// The minimum number of hops from the root to a node. Nodes that are not reachable from the
+    /// root, e.g. the `std` standalone node, have no depth: they are colored as the root and are
+    /// not included in [`NodeColoringValues::indices_values`].
+    Depth,
+    /// Feature count
+    ///
+    /// The number of enabled features of a node.
+    FeatureCount,
+    /// Version count
+    ///
+    /// The number of versions of the crate of a node in the graph.
+    VersionCount,
 }
 
 impl From<NodeColoringScheme> for &'static str {
@@ -62,10 +91,38 @@ impl From<NodeColoringScheme> for &'static str {
             NodeColoringScheme::CumSum => "cumulative sum",
             NodeColoringScheme::DepCount => "dependency count",
             NodeColoringScheme::RevDepCount => "reverse dependency count",
+            NodeColoringScheme::PathCount => "path count",
+            NodeColoringScheme::Exclusive => "exclusive size",
+            NodeColoringScheme::SelfSize => "self size",
+            NodeColoringScheme::Depth => "depth",
+            NodeColoringScheme::FeatureCount => "feature count",
+            NodeColoringScheme::VersionCount => "version count",
         }
     }
 }
 
+/// Model of attributing the size of a dependency to its dependents in cumulative sums.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, strum::EnumString)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum Attribution {
+    /// Full attribution
+    ///
+    /// Every dependent counts the whole size of a dependency, i.e. the cumulative sum of a node is
+    /// the total size of all nodes reachable from it.
+    Full,
+    /// Equal split
+    ///
+    /// The size of a dependency is split equally among its direct dependents.
+    #[default]
+    EqualSplit,
+    /// Proportional split
+    ///
+    /// The size of a dependency is split among its direct dependents proportionally to their own
+    /// sizes, or equally if none of them has a size.
+    Proportional,
+}
+
 /// Gradient for coloring nodes.
 ///
 /// This implements the [`Gradient`] trait to provide colors based on input values of type `Option<f64>`.
@@ -127,31 +184,162 @@ impl Gradient for NodeColoringGradient {
         if let Some(input) = input {
             let input = input.clamp(0.0, 1.0);
             let input = if inverse { 1.0 - input } else { input };
-            let mut color = colorous::Gradient::from(*self).eval_continuous(input);
+            let color = colorous::Gradient::from(*self).eval_continuous(input);
 
             if dark_mode {
-                let mut hsl: colorsys::Hsl =
-                    colorsys::Rgb::from(&(color.r, color.g, color.b)).into();
-                hsl.set_lightness(100.0 - hsl.lightness());
-                let (r, g, b) = colorsys::Rgb::from(hsl).into();
-                color = Color { r, g, b };
+                invert_lightness(color)
+            } else {
+                color
             }
-            color
         } else {
-            #[allow(clippy::collapsible_else_if)]
+            no_input_color(dark_mode)
+        }
+    }
+}
+
+/// Diverging gradient for coloring nodes.
+///
+/// This implements the [`Gradient`] trait to provide colors based on signed input values of type
+/// `Option<f64>` between -1.0 and 1.0, centred at 0.0. Positive values are colored towards the
+/// first color of the name, e.g. red for [`RdBu`](Self::RdBu), and negative values towards the
+/// last.
+#[derive(Deserialize, Debug, Default, Clone, Copy, strum::EnumString)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum NodeColoringDivergingGradient {
+    /// Red-Blue
+    #[default]
+    RdBu,
+    /// Red-Grey
+    RdGy,
+    /// Red-Yellow-Blue
+    RdYlBu,
+    /// Red-Yellow-Green
+    RdYlGn,
+    /// Pink-Yellow-Green
+    PiYG,
+    /// Purple-Green
+    PRGn,
+    /// Brown-Blue-Green
+    BrBG,
+    /// Purple-Orange
+    PuOr,
+    /// Spectral
+    Spectral,
+}
+
+impl From<NodeColoringDivergingGradient> for colorous::Gradient {
+    fn from(value: NodeColoringDivergingGradient) -> Self {
+        use colorous::*;
+        match value {
+            NodeColoringDivergingGradient::RdBu => RED_BLUE,
+            NodeColoringDivergingGradient::RdGy => RED_GREY,
+            NodeColoringDivergingGradient::RdYlBu => RED_YELLOW_BLUE,
+            NodeColoringDivergingGradient::RdYlGn => RED_YELLOW_GREEN,
+            NodeColoringDivergingGradient::PiYG => PINK_GREEN,
+            NodeColoringDivergingGradient::PRGn => PURPLE_GREEN,
+            NodeColoringDivergingGradient::BrBG => BROWN_GREEN,
+            NodeColoringDivergingGradient::PuOr => PURPLE_ORANGE,
+            NodeColoringDivergingGradient::Spectral => SPECTRAL,
+        }
+    }
+}
+
+impl Gradient for NodeColoringDivergingGradient {
+    type Input = Option<f64>;
+
+    fn color(&self, input: Self::Input, dark_mode: bool, inverse: bool) -> Color {
+        if let Some(input) = input {
+            let input = input.clamp(-1.0, 1.0);
+            let input = if inverse { input } else { -input };
+            let color = colorous::Gradient::from(*self).eval_continuous((input + 1.0) / 2.0);
+
             if dark_mode {
-                Color {
-                    r: 0x00,
-                    g: 0x00,
-                    b: 0x00,
-                }
+                invert_lightness(color)
             } else {
-                Color {
-                    r: 0xff,
-                    g: 0xff,
-                    b: 0xff,
-                }
+                color
             }
+        } else {
+            no_input_color(dark_mode)
+        }
+    }
+}
+
+/// Categorical gradient for coloring nodes.
+///
+/// This implements the [`Gradient`] trait to provide distinct colors based on input categories of
+/// type `Option<usize>`, cycling through the palette if there are more categories than colors.
+#[derive(Deserialize, Debug, Default, Clone, Copy, strum::EnumString)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum NodeColoringCategoricalGradient {
+    /// Tableau10
+    #[default]
+    Tableau10,
+    /// Category10
+    Category10,
+    /// Set1
+    Set1,
+    /// Set2
+    Set2,
+    /// Dark2
+    Dark2,
+    /// Pastel1
+    Pastel1,
+}
+
+impl From<NodeColoringCategoricalGradient> for &'static [Color] {
+    fn from(value: NodeColoringCategoricalGradient) -> Self {
+        use colorous::*;
+        match value {
+            NodeColoringCategoricalGradient::Tableau10 => &TABLEAU10,
+            NodeColoringCategoricalGradient::Category10 => &CATEGORY10,
+            NodeColoringCategoricalGradient::Set1 => &SET1,
+            NodeColoringCategoricalGradient::Set2 => &SET2,
+            NodeColoringCategoricalGradient::Dark2 => &DARK2,
+            NodeColoringCategoricalGradient::Pastel1 => &PASTEL1,
+        }
+    }
+}
+
+impl Gradient for NodeColoringCategoricalGradient {
+    type Input = Option<usize>;
+
+    fn color(&self, input: Self::Input, dark_mode: bool, _inverse: bool) -> Color {
+        if let Some(input) = input {
+            let palette: &[Color] = (*self).into();
+            let color = palette[input % palette.len()];
+
+            if dark_mode {
+                invert_lightness(color)
+            } else {
+                color
+            }
+        } else {
+            no_input_color(dark_mode)
+        }
+    }
+}
+
+fn invert_lightness(color: Color) -> Color {
+    let mut hsl: colorsys::Hsl = colorsys::Rgb::from(&(color.r, color.g, color.b)).into();
+    hsl.set_lightness(100.0 - hsl.lightness());
+    let (r, g, b) = colorsys::Rgb::from(hsl).into();
+    Color { r, g, b }
+}
+
+fn no_input_color(dark_mode: bool) -> Color {
+    if dark_mode {
+        Color {
+            r: 0x00,
+            g: 0x00,
+            b: 0x00,
+        }
+    } else {
+        Color {
+            r: 0xff,
+            g: 0xff,
+            b: 0xff,
         }
     }
 }
@@ -171,16 +359,24 @@ pub struct NodeColoringValues {
 
 impl NodeColoringValues {
     /// Create new coloring values for the given graph and scheme.
-    pub fn new(graph: &Graph, scheme: NodeColoringScheme) -> Self {
+    ///
+    /// The attribution model only applies to [`NodeColoringScheme::CumSum`].
+    pub fn new(graph: &Graph, scheme: NodeColoringScheme, attribution: Attribution) -> Self {
         match scheme {
-            NodeColoringScheme::CumSum => Self::cum_sums(graph),
+            NodeColoringScheme::CumSum => Self::cum_sums(graph, attribution),
             NodeColoringScheme::DepCount => Self::dep_counts(graph),
             NodeColoringScheme::RevDepCount => Self::rev_dep_counts(graph),
+            NodeColoringScheme::PathCount => Self::path_counts(graph),
+            NodeColoringScheme::Exclusive => Self::exclusive_sizes(graph),
+            NodeColoringScheme::SelfSize => Self::self_sizes(graph),
+            NodeColoringScheme::Depth => Self::depths(graph),
+            NodeColoringScheme::FeatureCount => Self::feature_counts(graph),
+            NodeColoringScheme::VersionCount => Self::version_counts(graph),
         }
     }
 
-    /// Create cumulative sum coloring values for the given graph.
-    fn cum_sums(graph: &Graph) -> Self {
+    /// Create cumulative sum coloring values for the given graph and attribution model.
+    fn cum_sums(graph: &Graph, attribution: Attribution) -> Self {
         let mut values = vec![0; graph.node_capacity()];
 
         for (index, size) in graph
@@ -190,12 +386,46 @@ impl NodeColoringValues {
             values[index] = size;
         }
 
-        let nodes: Vec<usize> = graph.topo().collect();
-
-        for node in nodes.iter().rev() {
-            let sources: Vec<_> = graph.neighbors(*node, false).collect();
-            for source in sources.iter() {
-                values[*source] += values[*node] / sources.len();
+        match attribution {
+            Attribution::Full => {
+                let sizes = values;
+                values = vec![0; graph.node_capacity()];
+
+                let mut visited = vec![usize::MAX; graph.node_capacity()];
+                for index in graph.node_indices() {
+                    let mut stack = vec![index];
+                    visited[index] = index;
+                    while let Some(node) = stack.pop() {
+                        values[index] += sizes[node];
+                        for target in graph.neighbors(node, true) {
+                            if visited[target] != index {
+                                visited[target] = index;
+                                stack.push(target);
+                            }
+                        }
+                    }
+                }
+            }
+            Attribution::EqualSplit | Attribution::Proportional => {
+                let nodes: Vec<usize> = graph.topo().collect();
+
+                for node in nodes.iter().rev() {
+                    let sources: Vec<_> = graph.neighbors(*node, false).collect();
+                    let total: usize = sources
+                        .iter()
+                        .map(|s| graph.size(*s).unwrap_or_default())
+                        .sum();
+                    let is_proportional = attribution == Attribution::Proportional && total > 0;
+
+                    for source in sources.iter() {
+                        values[*source] += if is_proportional {
+                            let size = graph.size(*source).unwrap_or_default();
+                            (values[*node] as u128 * size as u128 / total as u128) as usize
+                        } else {
+                            values[*node] / sources.len()
+                        };
+                    }
+                }
             }
         }
 
@@ -257,6 +487,140 @@ impl NodeColoringValues {
         }
     }
 
+    /// Create path count coloring values for the given graph.
+    fn path_counts(graph: &Graph) -> Self {
+        let values = graph.path_counts();
+        let max = *values.iter().max().unwrap();
+        let indices = graph.node_indices().collect();
+
+        Self {
+            indices,
+            values,
+            gamma: 0.25,
+            max,
+            scheme: NodeColoringScheme::PathCount,
+        }
+    }
+
+    /// Create exclusive size coloring values for the given graph.
+    fn exclusive_sizes(graph: &Graph) -> Self {
+        let mut values = vec![0; graph.node_capacity()];
+
+        for index in graph.node_indices() {
+            values[index] = graph.exclusive_size(index);
+        }
+
+        let max = *values.iter().max().unwrap();
+        let indices = graph.node_indices().collect();
+
+        Self {
+            indices,
+            values,
+            gamma: 0.25,
+            max,
+            scheme: NodeColoringScheme::Exclusive,
+        }
+    }
+
+    /// Create self size coloring values for the given graph.
+    fn self_sizes(graph: &Graph) -> Self {
+        let mut values = vec![0; graph.node_capacity()];
+
+        for index in graph.node_indices() {
+            values[index] = graph.size(index).unwrap_or_default();
+        }
+
+        let max = *values.iter().max().unwrap();
+        let indices = graph.node_indices().collect();
+
+        Self {
+            indices,
+            values,
+            gamma: 0.25,
+            max,
+            scheme: NodeColoringScheme::SelfSize,
+        }
+    }
+
+    /// Create depth coloring values for the given graph.
+    fn depths(graph: &Graph) -> Self {
+        let mut values = vec![None; graph.node_capacity()];
+        let mut queue = VecDeque::from([(graph.root(), 0)]);
+        values[graph.root()] = Some(0);
+
+        while let Some((node, depth)) = queue.pop_front() {
+            for target in graph.neighbors(node, true) {
+                if values[target].is_none() {
+                    values[target] = Some(depth + 1);
+                    queue.push_back((target, depth + 1));
+                }
+            }
+        }
+
+        // Nodes not reachable from the root have no depth
+        let indices = graph
+            .node_indices()
+            .filter(|&i| values[i].is_some())
+            .collect();
+        let values: Vec<usize> = values.into_iter().map(Option::unwrap_or_default).collect();
+        let max = *values.iter().max().unwrap();
+
+        Self {
+            indices,
+            values,
+            gamma: 1.0,
+            max,
+            scheme: NodeColoringScheme::Depth,
+        }
+    }
+
+    /// Create feature count coloring values for the given graph.
+    fn feature_counts(graph: &Graph) -> Self {
+        let mut values = vec![0; graph.node_capacity()];
+
+        for index in graph.node_indices() {
+            values[index] = graph.node_weight(index).features().len();
+        }
+
+        let max = *values.iter().max().unwrap();
+        let indices = graph.node_indices().collect();
+
+        Self {
+            indices,
+            values,
+            gamma: 0.5,
+            max,
+            scheme: NodeColoringScheme::FeatureCount,
+        }
+    }
+
+    /// Create version count coloring values for the given graph.
+    fn version_counts(graph: &Graph) -> Self {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for index in graph.node_indices() {
+            *counts
+                .entry(graph.node_id(index).name().to_string())
+                .or_default() += 1;
+        }
+
+        let mut values = vec![0; graph.node_capacity()];
+
+        for index in graph.node_indices() {
+            values[index] = counts[graph.node_id(index).name()];
+        }
+
+        let max = *values.iter().max().unwrap();
+        let indices = graph.node_indices().collect();
+
+        Self {
+            indices,
+            values,
+            gamma: 1.0,
+            max,
+            scheme: NodeColoringScheme::VersionCount,
+        }
+    }
+
     /// Get an iterator over the node indices of the graph as it was in [`new`](Self::new) and their
     /// corresponding values.
     pub fn indices_values(&self) -> impl Iterator<Item = (usize, usize)> {
@@ -273,6 +637,12 @@ impl NodeColoringValues {
         self.gamma
     }
 
+    /// Set the maximum value, e.g. to share the same color scale across multiple graphs. Values
+    /// above the maximum are clamped to it.
+    pub fn set_max(&mut self, max: usize) {
+        self.max = max;
+    }
+
     /// Get the maximum value.
     pub fn max(&self) -> usize {
         self.max
@@ -298,7 +668,57 @@ impl Values for Option<NodeColoringValues> {
     }
 
     fn output(&self, index: usize) -> Self::Output {
-        self.as_ref()
-            .map(|v| (v.values[index] as f64 / v.max as f64).powf(v.gamma))
+        self.as_ref().map(|v| {
+            if v.max == 0 {
+                return 0.0;
+            }
+
+            (v.values[index] as f64 / v.max as f64)
+                .min(1.0)
+                .powf(v.gamma)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{GraphBuilder, NodeId, Source};
+
+    /// `r` depends on `x` and `y`, which both depend on `z`.
+    fn graph() -> Graph {
+        let mut builder = GraphBuilder::new();
+        let mut add = |name, size| {
+            let id = NodeId::new(name, "1.0.0", Some(Source::Registry(None)));
+            builder.add_node(id, &[], Some(size))
+        };
+        let (r, x, y, z) = (add("r", 10), add("x", 30), add("y", 10), add("z", 100));
+        builder.add_edge(r, x, &[]);
+        builder.add_edge(r, y, &[]);
+        builder.add_edge(x, z, &[]);
+        builder.add_edge(y, z, &[]);
+        builder.build().unwrap()
+    }
+
+    fn cum_sums(attribution: Attribution) -> Vec<usize> {
+        let values = NodeColoringValues::new(&graph(), NodeColoringScheme::CumSum, attribution);
+        let mut indices_values = values.indices_values().collect::<Vec<_>>();
+        indices_values.sort();
+        indices_values.into_iter().map(|(_, v)| v).collect()
+    }
+
+    #[test]
+    fn full_attribution_counts_shared_dependencies_in_full() {
+        assert_eq!(cum_sums(Attribution::Full), [150, 130, 110, 100]);
+    }
+
+    #[test]
+    fn equal_split_attribution() {
+        assert_eq!(cum_sums(Attribution::EqualSplit), [150, 80, 60, 100]);
+    }
+
+    #[test]
+    fn proportional_attribution_splits_by_dependent_sizes() {
+        assert_eq!(cum_sums(Attribution::Proportional), [150, 105, 35, 100]);
     }
 }
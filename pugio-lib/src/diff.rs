@@ -0,0 +1,537 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    coloring::{Gradient, Values},
+    graph::{DotOptions, Graph, NodeId},
+    template::Templating,
+};
+
+/// Kind of change of a node between two graphs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    /// The crate only exists in the new graph.
+    Added,
+    /// The crate only exists in the old graph.
+    Removed,
+    /// The crate exists in both graphs with a different version or source.
+    Upgraded,
+    /// The crate exists in both graphs with the same version and source, but a different size.
+    Resized,
+    /// The crate exists in both graphs with the same version, source and size.
+    Unchanged,
+}
+
+impl From<DiffKind> for &'static str {
+    fn from(value: DiffKind) -> Self {
+        match value {
+            DiffKind::Added => "added",
+            DiffKind::Removed => "removed",
+            DiffKind::Upgraded => "upgraded",
+            DiffKind::Resized => "resized",
+            DiffKind::Unchanged => "unchanged",
+        }
+    }
+}
+
+/// Change of a node between two graphs.
+#[derive(Debug, Clone, Copy)]
+pub struct NodeDiff {
+    kind: DiffKind,
+    old_size: Option<usize>,
+    new_size: Option<usize>,
+}
+
+impl NodeDiff {
+    /// Get the kind of change.
+    pub fn kind(&self) -> DiffKind {
+        self.kind
+    }
+
+    /// Get the size in the old graph, if the crate exists in it.
+    pub fn old_size(&self) -> Option<usize> {
+        self.old_size
+    }
+
+    /// Get the size in the new graph, if the crate exists in it.
+    pub fn new_size(&self) -> Option<usize> {
+        self.new_size
+    }
+
+    /// Get the signed size delta from the old graph to the new graph.
+    pub fn delta(&self) -> isize {
+        self.new_size.unwrap_or_default() as isize - self.old_size.unwrap_or_default() as isize
+    }
+}
+
+/// Difference between two dependency graphs, e.g. before and after a dependency bump.
+///
+/// Nodes are matched by their [`NodeId`]s, i.e. package name, version and source. Any remaining
+/// nodes with the same short name in both graphs are matched as [`DiffKind::Upgraded`].
+///
+/// The difference is represented by a merged graph, which is the new graph with the removed nodes
+/// of the old graph and their edges added back. It implements both [`Values`] and [`Templating`]
+/// to output the merged graph with size deltas in labels, see [`output_dot`](Self::output_dot).
+///
+/// ```
+/// # use pugio_lib::graph::Graph;
+/// use pugio_lib::diff::{DiffKind, GraphDiff};
+///
+/// fn print_upgraded(old: &Graph, new: &Graph) {
+///     let diff = GraphDiff::new(old, new);
+///
+///     for index in diff.indices(DiffKind::Upgraded) {
+///         let old_extra = diff.old_extra(index).unwrap();
+///         let node = diff.graph().node_weight(index);
+///         println!("{}: {old_extra} -> {}", node.short(), node.extra());
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct GraphDiff {
+    graph: Graph,
+    diffs: Vec<Option<NodeDiff>>,
+    old_extras: HashMap<usize, String>,
+    max: usize,
+}
+
+impl GraphDiff {
+    /// Create the difference from the `old` graph to the `new` graph.
+    pub fn new(old: &Graph, new: &Graph) -> Self {
+        let mut graph = new.clone();
+
+        let new_map: HashMap<NodeId, usize> =
+            new.node_indices().map(|i| (new.node_id(i), i)).collect();
+
+        // Index of the old graph to index of the merged graph
+        let mut index_map = HashMap::with_capacity(old.node_count());
+        let mut unmatched_old: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        for index in old.node_indices() {
+            let node = old.node_weight(index);
+            match new_map.get(&old.node_id(index)) {
+                Some(new_index) => {
+                    index_map.insert(index, *new_index);
+                }
+                None => unmatched_old.entry(node.short()).or_default().push(index),
+            }
+        }
+
+        let mut unmatched_new: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        let mut matched_new = vec![false; new.node_capacity()];
+        for index in index_map.values() {
+            matched_new[*index] = true;
+        }
+        for index in new.node_indices().filter(|i| !matched_new[*i]) {
+            let short = new.node_weight(index).short();
+            unmatched_new.entry(short).or_default().push(index);
+        }
+
+        let mut old_extras = HashMap::new();
+        for (short, old_indices) in unmatched_old.iter_mut() {
+            if let Some(new_indices) = unmatched_new.get_mut(short) {
+                let len = old_indices.len().min(new_indices.len());
+                for (old_index, new_index) in old_indices.drain(..len).zip(new_indices.drain(..len))
+                {
+                    index_map.insert(old_index, new_index);
+                    let extra = old.node_weight(old_index).extra().to_string();
+                    old_extras.insert(new_index, extra);
+                }
+            }
+        }
+
+        for old_index in unmatched_old.into_values().flatten() {
+            let weight = old.node_weight(old_index).clone();
+            let index = graph.add_node(weight, old.size(old_index));
+            index_map.insert(old_index, index);
+        }
+
+        let mut diffs = vec![None; graph.node_capacity()];
+        for index in new.node_indices() {
+            diffs[index] = Some(NodeDiff {
+                kind: DiffKind::Added,
+                old_size: None,
+                new_size: new.size(index),
+            });
+        }
+
+        for (old_index, index) in index_map.iter() {
+            let old_size = old.size(*old_index);
+            let diff = diffs[*index].get_or_insert(NodeDiff {
+                kind: DiffKind::Removed,
+                old_size: None,
+                new_size: None,
+            });
+            diff.old_size = old_size;
+
+            if diff.kind == DiffKind::Added {
+                diff.kind = if old_extras.contains_key(index) {
+                    DiffKind::Upgraded
+                } else if diff.old_size != diff.new_size {
+                    DiffKind::Resized
+                } else {
+                    DiffKind::Unchanged
+                };
+            }
+        }
+
+        for index in old.node_indices() {
+            for target in old.neighbors(index, true) {
+                let (source, target_index) = (index_map[&index], index_map[&target]);
+                let is_removed = |i: usize| diffs[i].is_some_and(|d| d.kind == DiffKind::Removed);
+                if is_removed(source) || is_removed(target_index) {
+                    let weight = old.edge_weight(index, target).clone();
+                    graph.add_edge(source, target_index, weight);
+                }
+            }
+        }
+
+        let max = diffs
+            .iter()
+            .flatten()
+            .map(|d| d.delta().unsigned_abs())
+            .max()
+            .unwrap_or_default();
+
+        Self {
+            graph,
+            diffs,
+            old_extras,
+            max,
+        }
+    }
+
+    /// Get the merged graph.
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    /// Get the change of the node at the given index of the merged graph.
+    ///
+    /// # Panics
+    /// Panics if the node does not exist in the merged graph.
+    pub fn node_diff(&self, index: usize) -> NodeDiff {
+        self.diffs[index].unwrap()
+    }
+
+    /// Get an iterator over the node indices of the merged graph with the given kind of change.
+    pub fn indices(&self, kind: DiffKind) -> impl Iterator<Item = usize> {
+        self.graph
+            .node_indices()
+            .filter(move |i| self.diffs[*i].is_some_and(|d| d.kind == kind))
+    }
+
+    /// Get the extra information of the crate in the old graph, if it is
+    /// [`DiffKind::Upgraded`].
+    pub fn old_extra(&self, index: usize) -> Option<&str> {
+        self.old_extras.get(&index).map(String::as_str)
+    }
+
+    /// Output the merged graph in DOT format with the given options, coloring values and gradient,
+    /// where removed nodes and their edges are ghosted, and added nodes are emphasised.
+    ///
+    /// The `values` parameter can be either this difference itself, colored by the magnitude of
+    /// size deltas, or [`DeltaValues`], colored by the signed size deltas.
+    ///
+    /// ```
+    /// # use pugio_lib::graph::Graph;
+    /// use pugio_lib::coloring::NodeColoringDivergingGradient;
+    /// use pugio_lib::diff::{DeltaValues, GraphDiff};
+    ///
+    /// fn output(old: &Graph, new: &Graph) -> String {
+    ///     let diff = GraphDiff::new(old, new);
+    ///     let values = DeltaValues::new(&diff);
+    ///     let gradient = NodeColoringDivergingGradient::RdBu;
+    ///
+    ///     diff.output_dot(&Default::default(), &values, &gradient)
+    /// }
+    /// ```
+    pub fn output_dot<T, S, G>(&self, dot_options: &DotOptions, values: &S, gradient: &G) -> String
+    where
+        S: Values<Context = (), Value = NodeDiff, Output = T>,
+        G: Gradient<Input = T>,
+    {
+        let mut dot_options = dot_options.clone();
+
+        for index in self.graph.node_indices() {
+            let attributes = match self.node_diff(index).kind {
+                DiffKind::Added => r#"style = "filled,bold""#,
+                DiffKind::Removed => r##"style = "dashed" fontcolor = "#7F7F7F""##,
+                _ => continue,
+            };
+            dot_options
+                .node_attributes
+                .entry(index)
+                .or_insert_with(|| attributes.to_string());
+
+            if self.node_diff(index).kind == DiffKind::Removed {
+                let edges = self
+                    .graph
+                    .neighbors(index, true)
+                    .map(|t| (index, t))
+                    .chain(self.graph.neighbors(index, false).map(|s| (s, index)))
+                    .collect::<Vec<_>>();
+                for edge in edges {
+                    dot_options
+                        .edge_attributes
+                        .entry(edge)
+                        .or_insert_with(|| r#"style = "dashed""#.to_string());
+                }
+            }
+        }
+
+        self.graph.output_dot(&dot_options, self, values, gradient)
+    }
+}
+
+impl Values for GraphDiff {
+    type Context = ();
+    type Value = NodeDiff;
+    type Output = Option<f64>;
+
+    fn context(&self) -> Self::Context {}
+
+    fn value(&self, index: usize) -> Self::Value {
+        self.node_diff(index)
+    }
+
+    /// The magnitude of the size delta relative to the maximum, or `None` if unchanged.
+    fn output(&self, index: usize) -> Self::Output {
+        let diff = self.node_diff(index);
+        (diff.kind != DiffKind::Unchanged && self.max > 0)
+            .then(|| (diff.delta().unsigned_abs() as f64 / self.max as f64).powf(0.5))
+    }
+}
+
+/// Values for coloring nodes of a [`GraphDiff`] by their signed size deltas.
+///
+/// This implements the [`Values`] trait to provide outputs between -1.0 and 1.0, centred at 0.0,
+/// to be used with a diverging gradient such as
+/// [`NodeColoringDivergingGradient`](crate::coloring::NodeColoringDivergingGradient), so that
+/// growth and shrinkage are colored in opposite directions with the same visual weight.
+#[derive(Debug)]
+pub struct DeltaValues<'a> {
+    diff: &'a GraphDiff,
+    gamma: f64,
+}
+
+impl<'a> DeltaValues<'a> {
+    /// Create new delta values for the given difference.
+    pub fn new(diff: &'a GraphDiff) -> Self {
+        Self { diff, gamma: 0.5 }
+    }
+
+    /// Set the gamma value, clamped between 0.0 and 1.0.
+    pub fn set_gamma(&mut self, gamma: f64) {
+        self.gamma = gamma.clamp(0.0, 1.0);
+    }
+
+    /// Get the gamma value.
+    pub fn gamma(&self) -> f64 {
+        self.gamma
+    }
+}
+
+impl Values for DeltaValues<'_> {
+    type Context = ();
+    type Value = NodeDiff;
+    type Output = Option<f64>;
+
+    fn context(&self) -> Self::Context {}
+
+    fn value(&self, index: usize) -> Self::Value {
+        self.diff.node_diff(index)
+    }
+
+    fn output(&self, index: usize) -> Self::Output {
+        if self.diff.max == 0 {
+            return Some(0.0);
+        }
+        let delta = self.diff.node_diff(index).delta();
+        let magnitude = (delta.unsigned_abs() as f64 / self.diff.max as f64).powf(self.gamma);
+        Some(magnitude.copysign(delta as f64))
+    }
+}
+
+impl Templating for GraphDiff {
+    type Context = ();
+    type Value = NodeDiff;
+
+    fn node(
+        &self,
+        graph: &Graph,
+        index: usize,
+        value: Self::Value,
+        _context: Self::Context,
+    ) -> (String, String) {
+        let node = graph.node_weight(index);
+        let format = |size: Option<usize>| {
+            size.map(|s| humansize::format_size(s, humansize::BINARY))
+                .unwrap_or_else(|| "-".to_string())
+        };
+        let delta = value.delta();
+        let delta = format!(
+            "{}{}",
+            if delta < 0 { "-" } else { "+" },
+            humansize::format_size(delta.unsigned_abs(), humansize::BINARY)
+        );
+
+        let label = match value.kind {
+            DiffKind::Unchanged => node.short().to_string(),
+            _ => format!("{}\n{delta}", node.short()),
+        };
+
+        let full = match self.old_extra(index) {
+            Some(old_extra) => format!("{} ({old_extra} -> {})", node.short(), node.extra()),
+            None => node.full().to_string(),
+        };
+        let kind: &'static str = value.kind.into();
+        let tooltip = format!(
+            "{full}\n{kind}\n{} -> {} ({delta})",
+            format(value.old_size),
+            format(value.new_size)
+        );
+
+        (label, tooltip)
+    }
+
+    fn edge(&self, graph: &Graph, source: usize, target: usize) -> (String, String) {
+        let source = graph.node_weight(source).short();
+        let target = graph.node_weight(target).short();
+        (String::new(), format!("{source} -> {target}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{GraphBuilder, Source};
+
+    /// Graph of a workspace checked out at `{parent}/project`, with a vendored path dependency
+    /// next to it.
+    fn checkout(parent: &str) -> Graph {
+        let root = format!("{parent}/project");
+        let mut builder = GraphBuilder::new();
+        let app = builder.add_node(
+            NodeId::new("app", "0.1.0", Some(Source::Path(format!("{root}/app")))),
+            &[],
+            Some(4096),
+        );
+        let util = builder.add_node(
+            NodeId::new("util", "0.1.0", Some(Source::Path(format!("{root}/util")))),
+            &[],
+            Some(2048),
+        );
+        let vendored = builder.add_node(
+            NodeId::new(
+                "vendored",
+                "1.0.0",
+                Some(Source::Path(format!("{parent}/vendored"))),
+            ),
+            &[],
+            Some(1024),
+        );
+        let serde = builder.add_node(
+            NodeId::new("serde", "1.0.0", Some(Source::Registry(None))),
+            &[],
+            Some(1024),
+        );
+        builder.add_edge(app, util, &[]);
+        builder.add_edge(app, vendored, &[]);
+        builder.add_edge(util, serde, &[]);
+        builder.set_workspace_root(&root);
+        builder.set_workspace_members(&[&format!("{root}/app"), &format!("{root}/util")]);
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn classifies_node_changes() {
+        let old = GraphBuilder::star(&[
+            ("app", "0.1.0", 1000),
+            ("serde", "1.0.0", 2000),
+            ("tokio", "1.0.0", 3000),
+            ("log", "0.4.0", 500),
+        ]);
+        let new = GraphBuilder::star(&[
+            ("app", "0.1.0", 1200),
+            ("serde", "1.0.0", 2000),
+            ("tokio", "2.0.0", 2500),
+            ("regex", "1.0.0", 4000),
+        ]);
+        let diff = GraphDiff::new(&old, &new);
+
+        let kinds = ["app", "serde", "tokio", "log", "regex"]
+            .map(|short| diff.node_diff(diff.graph().index_of(short)).kind());
+        assert_eq!(
+            kinds,
+            [
+                DiffKind::Resized,
+                DiffKind::Unchanged,
+                DiffKind::Upgraded,
+                DiffKind::Removed,
+                DiffKind::Added,
+            ]
+        );
+
+        let tokio = diff.graph().index_of("tokio");
+        assert_eq!(diff.old_extra(tokio), Some("v1.0.0"));
+        assert_eq!(diff.node_diff(tokio).delta(), -500);
+        assert_eq!(diff.old_extra(diff.graph().index_of("serde")), None);
+
+        let log = diff.graph().index_of("log");
+        assert_eq!(diff.node_diff(log).old_size(), Some(500));
+        assert_eq!(diff.node_diff(log).new_size(), None);
+        assert!(diff.graph().neighbors(0, true).any(|i| i == log));
+    }
+
+    #[test]
+    fn delta_values_diverge_from_midpoint() {
+        use crate::coloring::NodeColoringDivergingGradient;
+
+        let old = GraphBuilder::star(&[
+            ("app", "0.1.0", 1000),
+            ("serde", "1.0.0", 2000),
+            ("tokio", "1.0.0", 3000),
+            ("log", "0.4.0", 500),
+        ]);
+        let new = GraphBuilder::star(&[
+            ("app", "0.1.0", 1500),
+            ("serde", "1.0.0", 2000),
+            ("tokio", "1.0.0", 2500),
+            ("regex", "1.0.0", 4000),
+        ]);
+        let diff = GraphDiff::new(&old, &new);
+        let values = DeltaValues::new(&diff);
+        let output = |short| values.output(diff.graph().index_of(short)).unwrap();
+
+        assert_eq!(output("regex"), 1.0);
+        assert!(output("app") > 0.0);
+        assert_eq!(output("serde"), 0.0);
+        assert!(output("tokio") < 0.0);
+        assert!(output("log") < 0.0);
+        assert_eq!(output("app"), -output("tokio"));
+
+        // Growth is red and shrinkage is blue, around the neutral midpoint
+        let gradient = NodeColoringDivergingGradient::RdBu;
+        let color = |short| gradient.color(Some(output(short)), false, false);
+        let hex = |color| format!("{color:X}");
+        for short in ["regex", "app"] {
+            assert!(color(short).r > color(short).b, "{short}");
+        }
+        for short in ["tokio", "log"] {
+            assert!(color(short).b > color(short).r, "{short}");
+        }
+        let midpoint = colorous::RED_BLUE.eval_continuous(0.5);
+        assert_eq!(hex(color("serde")), hex(midpoint));
+        assert_eq!(
+            hex(gradient.color(Some(output("app")), false, true)),
+            hex(color("tokio"))
+        );
+    }
+
+    #[test]
+    fn checkouts_differing_only_in_root_path_are_unchanged() {
+        let diff = GraphDiff::new(&checkout("/a"), &checkout("/b/worktrees"));
+
+        assert_eq!(diff.graph().node_count(), 4);
+        assert_eq!(diff.indices(DiffKind::Unchanged).count(), 4);
+    }
+}
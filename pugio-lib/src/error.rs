@@ -5,3 +5,68 @@ use thiserror::Error;
 #[derive(Error, Debug)]
 #[error("invalid template: {0}")]
 pub struct TemplateError(#[from] tinytemplate::error::Error);
+
+/// This type represents errors that occur during [`Graph::group`](crate::graph::Graph::group).
+#[derive(Error, Debug)]
+pub enum GroupError {
+    /// The group has no members.
+    #[error("group {0} has no members")]
+    Empty(String),
+    /// The group name is the short name of an existing crate that is not a member.
+    #[error("group {0} conflicts with an existing crate")]
+    Conflict(String),
+    /// The group would create a cycle, as a member depends on another member through a non-member.
+    #[error("group {0} would create a cycle")]
+    Cycle(String),
+}
+
+/// This type represents errors that occur when a [`Graph`](crate::graph::Graph) is constructed
+/// from external data that violates its invariants.
+#[derive(Error, Debug)]
+pub enum GraphError {
+    /// The schema version is not supported.
+    #[error("unsupported schema version {0}")]
+    UnsupportedVersion(u32),
+    /// A node index is not less than the number of nodes.
+    #[error("invalid node index {0}")]
+    InvalidNode(usize),
+    /// A node index is used by more than one node.
+    #[error("duplicate node index {0}")]
+    DuplicateNode(usize),
+    /// A node index does not refer to any node.
+    #[error("missing node index {0}")]
+    MissingNode(usize),
+    /// The graph contains a cycle.
+    #[error("graph is not acyclic")]
+    Cyclic,
+    /// A node other than `std` is not reachable from the root.
+    #[error("node index {0} is not reachable from the root")]
+    Unreachable(usize),
+}
+
+/// This type represents errors that occur during [`Filter`](crate::filter::Filter) parsing.
+#[derive(Error, Debug)]
+pub enum FilterError {
+    /// An unexpected token at the given byte position.
+    #[error("unexpected token at position {0}")]
+    UnexpectedToken(usize),
+    /// The expression ends unexpectedly.
+    #[error("unexpected end of expression")]
+    UnexpectedEnd,
+    /// The attribute does not exist.
+    #[error("unknown attribute {0}")]
+    UnknownAttribute(String),
+    /// The operator is not supported for the attribute.
+    #[error("operator {1} is not supported for attribute {0}")]
+    InvalidOperator(String, &'static str),
+    /// The literal is malformed.
+    #[error("invalid literal {0}")]
+    InvalidLiteral(String),
+    /// A percentage literal is used for an attribute that is not a size.
+    #[error("percentage is not supported for attribute {0}")]
+    InvalidPercentage(String),
+    /// The regex pattern is malformed.
+    #[cfg(feature = "regex")]
+    #[error("invalid regex: {0}")]
+    Regex(#[from] regex_lite::Error),
+}
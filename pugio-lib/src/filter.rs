@@ -0,0 +1,738 @@
+/*!
+Filter expressions evaluated against node attributes, e.g.
+
+```text
+cum_sum < 10KiB && depth > 2 && !workspace
+```
+
+An expression is made of comparisons between a node attribute and a literal, bare boolean
+attributes, `!` negations, `&&` conjunctions, `||` disjunctions and parentheses, with the usual
+precedence of `!` over `&&` over `||`.
+
+| Attribute       | Type    | Description                                                  |
+|-----------------|---------|--------------------------------------------------------------|
+| `size`          | number  | self size, see [`NodeColoringScheme::SelfSize`]              |
+| `cum_sum`       | number  | cumulative sum, see [`NodeColoringScheme::CumSum`] and [`Filter::set_attribution`] |
+| `exclusive`     | number  | exclusive size, see [`NodeColoringScheme::Exclusive`]        |
+| `dep_count`     | number  | dependency count, see [`NodeColoringScheme::DepCount`]       |
+| `rev_dep_count` | number  | reverse dependency count, see [`NodeColoringScheme::RevDepCount`] |
+| `path_count`    | number  | path count, see [`NodeColoringScheme::PathCount`]            |
+| `depth`         | number  | minimum depth from the root, see [`NodeColoringScheme::Depth`] |
+| `feature_count` | number  | feature count, see [`NodeColoringScheme::FeatureCount`]      |
+| `name`          | string  | short name, e.g. `pugio_lib`                                 |
+| `full`          | string  | full name, e.g. `pugio_lib v1.0.0`                           |
+| `version`       | string  | version without the leading `v`, e.g. `1.0.0`                |
+| `source`        | string  | source kind, see [`Source::kind`], or empty if unknown       |
+| `features`      | strings | enabled features                                             |
+| `workspace`, `path`, `git`, `registry` | boolean | whether the source is of the kind   |
+| `root`, `std`   | boolean | whether the node is the root or the `std` standalone node    |
+
+Numbers support the comparison operators `==`, `!=`, `<`, `<=`, `>` and `>=`, and their literals
+may have a byte unit suffix, e.g. `21KiB` or `69KB`. The literals of size attributes, i.e. `size`,
+`cum_sum` and `exclusive`, may also be a percentage of the [total size](Graph::total_size), e.g.
+`cum_sum < 0.5%`. Strings support `==`, `!=` and `=~`, which matches a regex pattern with the
+`regex` feature and a prefix otherwise, with double-quoted literals, e.g. `name =~ "windows"`.
+Within literals, `\"`, `\\`, `\n` and `\t` are escapes, and any other backslash is kept verbatim,
+e.g. `name =~ "^windows_\w+"`. A comparison on `features` with `==` or `=~` is true if any feature
+satisfies it, and with `!=` if no feature equals the literal.
+
+As the `std` standalone node is not reachable from the root, it has no depth, see
+[`NodeColoringScheme::Depth`], and comparisons on its `depth` are always false.
+*/
+
+use std::str::FromStr;
+
+use crate::{
+    coloring::{Attribution, NodeColoringScheme, NodeColoringValues},
+    error::FilterError,
+    graph::Graph,
+};
+
+#[cfg(doc)]
+use crate::graph::Source;
+
+/// A parsed filter expression, which can be evaluated against the nodes of a [`Graph`].
+///
+/// ```
+/// # use pugio_lib::{filter::Filter, graph::Graph};
+/// fn remove_small(graph: &mut Graph) {
+///     let filter = Filter::parse("cum_sum < 10KiB && !root").unwrap();
+///     graph.remove_indices(filter.matched_indices(graph).into_iter());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Filter {
+    expr: Expr,
+    attribution: Attribution,
+}
+
+impl Filter {
+    /// Parse the given filter expression.
+    ///
+    /// # Errors
+    /// Returns an error if the expression is malformed, uses an unknown attribute, or applies an
+    /// operator to an attribute that does not support it.
+    pub fn parse(expr: &str) -> Result<Self, FilterError> {
+        let tokens = tokenize(expr)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        match parser.tokens.get(parser.pos) {
+            Some((_, position)) => Err(FilterError::UnexpectedToken(*position)),
+            None => Ok(Self {
+                expr,
+                attribution: Attribution::default(),
+            }),
+        }
+    }
+
+    /// Set the attribution model of `cum_sum`, equal split by default.
+    pub fn set_attribution(&mut self, attribution: Attribution) {
+        self.attribution = attribution;
+    }
+
+    /// Get the indices of the nodes of the given graph matching the filter.
+    pub fn matched_indices(&self, graph: &Graph) -> Vec<usize> {
+        let mut numbers = [const { None }; NUMBER_ATTRIBUTES.len()];
+        self.expr.prepare(graph, self.attribution, &mut numbers);
+        let total_size = graph.total_size();
+
+        graph
+            .node_indices()
+            .filter(|i| self.expr.eval(graph, *i, &numbers, total_size))
+            .collect()
+    }
+}
+
+impl FromStr for Filter {
+    type Err = FilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NumberAttribute {
+    Size,
+    CumSum,
+    Exclusive,
+    DepCount,
+    RevDepCount,
+    PathCount,
+    Depth,
+    FeatureCount,
+}
+
+const NUMBER_ATTRIBUTES: [(&str, NumberAttribute); 8] = [
+    ("size", NumberAttribute::Size),
+    ("cum_sum", NumberAttribute::CumSum),
+    ("exclusive", NumberAttribute::Exclusive),
+    ("dep_count", NumberAttribute::DepCount),
+    ("rev_dep_count", NumberAttribute::RevDepCount),
+    ("path_count", NumberAttribute::PathCount),
+    ("depth", NumberAttribute::Depth),
+    ("feature_count", NumberAttribute::FeatureCount),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StringAttribute {
+    Name,
+    Full,
+    Version,
+    Source,
+    Features,
+}
+
+const STRING_ATTRIBUTES: [(&str, StringAttribute); 5] = [
+    ("name", StringAttribute::Name),
+    ("full", StringAttribute::Full),
+    ("version", StringAttribute::Version),
+    ("source", StringAttribute::Source),
+    ("features", StringAttribute::Features),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BoolAttribute {
+    Source(&'static str),
+    Root,
+    Std,
+}
+
+const BOOL_ATTRIBUTES: [(&str, BoolAttribute); 6] = [
+    ("workspace", BoolAttribute::Source("workspace")),
+    ("path", BoolAttribute::Source("path")),
+    ("git", BoolAttribute::Source("git")),
+    ("registry", BoolAttribute::Source("registry")),
+    ("root", BoolAttribute::Root),
+    ("std", BoolAttribute::Std),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Match,
+}
+
+impl Operator {
+    fn as_str(self) -> &'static str {
+        match self {
+            Operator::Eq => "==",
+            Operator::Ne => "!=",
+            Operator::Lt => "<",
+            Operator::Le => "<=",
+            Operator::Gt => ">",
+            Operator::Ge => ">=",
+            Operator::Match => "=~",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Literal {
+    Number(usize),
+    Percent(f64),
+}
+
+#[derive(Debug, Clone)]
+enum Pattern {
+    Equal(String),
+    #[cfg(feature = "regex")]
+    Regex(regex_lite::Regex),
+    #[cfg(not(feature = "regex"))]
+    Prefix(String),
+}
+
+impl Pattern {
+    fn is_match(&self, s: &str) -> bool {
+        match self {
+            Pattern::Equal(p) => s == p,
+            #[cfg(feature = "regex")]
+            Pattern::Regex(r) => r.is_match(s),
+            #[cfg(not(feature = "regex"))]
+            Pattern::Prefix(p) => s.starts_with(p.as_str()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Bool(BoolAttribute),
+    Number(NumberAttribute, Operator, Literal),
+    String(StringAttribute, Pattern, bool),
+}
+
+impl Expr {
+    /// Compute the values of the number attributes used in the expression.
+    fn prepare(
+        &self,
+        graph: &Graph,
+        attribution: Attribution,
+        numbers: &mut [Option<Vec<Option<usize>>>],
+    ) {
+        match self {
+            Expr::And(l, r) | Expr::Or(l, r) => {
+                l.prepare(graph, attribution, numbers);
+                r.prepare(graph, attribution, numbers);
+            }
+            Expr::Not(e) => e.prepare(graph, attribution, numbers),
+            Expr::Number(attribute, _, _) => {
+                let position = NUMBER_ATTRIBUTES
+                    .iter()
+                    .position(|(_, a)| a == attribute)
+                    .unwrap();
+                numbers[position]
+                    .get_or_insert_with(|| number_values(graph, *attribute, attribution));
+            }
+            Expr::Bool(_) | Expr::String(..) => {}
+        }
+    }
+
+    fn eval(
+        &self,
+        graph: &Graph,
+        index: usize,
+        numbers: &[Option<Vec<Option<usize>>>],
+        total_size: usize,
+    ) -> bool {
+        match self {
+            Expr::And(l, r) => {
+                l.eval(graph, index, numbers, total_size)
+                    && r.eval(graph, index, numbers, total_size)
+            }
+            Expr::Or(l, r) => {
+                l.eval(graph, index, numbers, total_size)
+                    || r.eval(graph, index, numbers, total_size)
+            }
+            Expr::Not(e) => !e.eval(graph, index, numbers, total_size),
+            Expr::Bool(attribute) => match attribute {
+                BoolAttribute::Source(kind) => graph
+                    .node_weight(index)
+                    .source()
+                    .is_some_and(|s| s.kind() == *kind),
+                BoolAttribute::Root => index == graph.root(),
+                BoolAttribute::Std => Some(index) == graph.std(),
+            },
+            Expr::Number(attribute, operator, literal) => {
+                let position = NUMBER_ATTRIBUTES
+                    .iter()
+                    .position(|(_, a)| a == attribute)
+                    .unwrap();
+                let Some(value) = numbers[position].as_ref().unwrap()[index] else {
+                    return false;
+                };
+                let (value, literal) = match literal {
+                    Literal::Number(n) => (value as f64, *n as f64),
+                    Literal::Percent(p) => (value as f64, total_size as f64 * p / 100.0),
+                };
+                match operator {
+                    Operator::Eq => value == literal,
+                    Operator::Ne => value != literal,
+                    Operator::Lt => value < literal,
+                    Operator::Le => value <= literal,
+                    Operator::Gt => value > literal,
+                    Operator::Ge => value >= literal,
+                    Operator::Match => unreachable!(),
+                }
+            }
+            Expr::String(attribute, pattern, is_negated) => {
+                let node = graph.node_weight(index);
+                let is_match = match attribute {
+                    StringAttribute::Name => pattern.is_match(node.short()),
+                    StringAttribute::Full => pattern.is_match(node.full()),
+                    StringAttribute::Version => {
+                        let version = node.extra().split(' ').next().unwrap_or_default();
+                        pattern.is_match(version.strip_prefix('v').unwrap_or(version))
+                    }
+                    StringAttribute::Source => {
+                        pattern.is_match(node.source().map(|s| s.kind()).unwrap_or_default())
+                    }
+                    StringAttribute::Features => {
+                        node.features().keys().any(|f| pattern.is_match(f))
+                    }
+                };
+                is_match != *is_negated
+            }
+        }
+    }
+}
+
+fn number_values(
+    graph: &Graph,
+    attribute: NumberAttribute,
+    attribution: Attribution,
+) -> Vec<Option<usize>> {
+    let scheme = match attribute {
+        NumberAttribute::CumSum => NodeColoringScheme::CumSum,
+        NumberAttribute::Exclusive => NodeColoringScheme::Exclusive,
+        NumberAttribute::DepCount => NodeColoringScheme::DepCount,
+        NumberAttribute::RevDepCount => NodeColoringScheme::RevDepCount,
+        NumberAttribute::PathCount => NodeColoringScheme::PathCount,
+        NumberAttribute::Size => NodeColoringScheme::SelfSize,
+        NumberAttribute::Depth => NodeColoringScheme::Depth,
+        NumberAttribute::FeatureCount => NodeColoringScheme::FeatureCount,
+    };
+
+    let mut values = vec![None; graph.node_capacity()];
+    for (index, value) in NodeColoringValues::new(graph, scheme, attribution).indices_values() {
+        values[index] = Some(value);
+    }
+
+    values
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(Literal),
+    String(String),
+    Operator(Operator),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+fn tokenize(expr: &str) -> Result<Vec<(Token, usize)>, FilterError> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.char_indices().peekable();
+
+    while let Some((position, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '&' | '|' | '=' | '!' | '<' | '>' => {
+                let next = chars.peek().map(|(_, c)| *c);
+                let (token, is_pair) = match (c, next) {
+                    ('&', Some('&')) => (Token::And, true),
+                    ('|', Some('|')) => (Token::Or, true),
+                    ('=', Some('=')) => (Token::Operator(Operator::Eq), true),
+                    ('=', Some('~')) => (Token::Operator(Operator::Match), true),
+                    ('!', Some('=')) => (Token::Operator(Operator::Ne), true),
+                    ('<', Some('=')) => (Token::Operator(Operator::Le), true),
+                    ('>', Some('=')) => (Token::Operator(Operator::Ge), true),
+                    ('!', _) => (Token::Not, false),
+                    ('<', _) => (Token::Operator(Operator::Lt), false),
+                    ('>', _) => (Token::Operator(Operator::Gt), false),
+                    _ => return Err(FilterError::UnexpectedToken(position)),
+                };
+                if is_pair {
+                    chars.next();
+                }
+                token
+            }
+            '"' => {
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, 'n')) => string.push('\n'),
+                            Some((_, 't')) => string.push('\t'),
+                            Some((_, c @ ('"' | '\\'))) => string.push(c),
+                            // Keep unknown escapes verbatim, e.g. regex `\d`
+                            Some((_, c)) => {
+                                string.push('\\');
+                                string.push(c);
+                            }
+                            None => return Err(FilterError::UnexpectedEnd),
+                        },
+                        Some((_, c)) => string.push(c),
+                        None => return Err(FilterError::UnexpectedEnd),
+                    }
+                }
+                Token::String(string)
+            }
+            c if c.is_ascii_digit() => {
+                let mut end = position + 1;
+                while let Some((i, c)) = chars.peek().copied()
+                    && (c.is_ascii_alphanumeric() || c == '.')
+                {
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                if let Some((i, '%')) = chars.peek().copied() {
+                    end = i + 1;
+                    chars.next();
+                }
+                Token::Number(parse_number(&expr[position..end])?)
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = position + 1;
+                while let Some((i, c)) = chars.peek().copied()
+                    && (c.is_alphanumeric() || c == '_')
+                {
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                Token::Ident(expr[position..end].to_string())
+            }
+            _ => return Err(FilterError::UnexpectedToken(position)),
+        };
+        tokens.push((token, position));
+    }
+
+    Ok(tokens)
+}
+
+/// Parse a number literal with an optional byte unit suffix, e.g. `21KiB` or `69KB`, or a
+/// percentage, e.g. `0.5%`.
+fn parse_number(literal: &str) -> Result<Literal, FilterError> {
+    let invalid = || FilterError::InvalidLiteral(literal.to_string());
+
+    if let Some(percent) = literal.strip_suffix('%') {
+        return match percent.parse::<f64>() {
+            Ok(p) if p.is_finite() && p >= 0.0 => Ok(Literal::Percent(p)),
+            _ => Err(invalid()),
+        };
+    }
+
+    let split_at = literal
+        .find(|c: char| c.is_ascii_alphabetic())
+        .unwrap_or(literal.len());
+    let (number, unit) = literal.split_at(split_at);
+
+    let multiplier: u64 = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1000,
+        "kib" => 1 << 10,
+        "m" | "mb" => 1000 * 1000,
+        "mib" => 1 << 20,
+        "g" | "gb" => 1000 * 1000 * 1000,
+        "gib" => 1 << 30,
+        _ => return Err(invalid()),
+    };
+
+    let number = if let Ok(number) = number.parse::<u64>() {
+        number
+            .checked_mul(multiplier)
+            .map(|n| n as usize)
+            .ok_or_else(invalid)?
+    } else {
+        let number: f64 = number.parse().map_err(|_| invalid())?;
+        (number * multiplier as f64) as usize
+    };
+    Ok(Literal::Number(number))
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Result<(Token, usize), FilterError> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or(FilterError::UnexpectedEnd)?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn is_next(&mut self, token: &Token) -> bool {
+        let is_next = self.tokens.get(self.pos).is_some_and(|(t, _)| t == token);
+        if is_next {
+            self.pos += 1;
+        }
+        is_next
+    }
+
+    fn or(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.and()?;
+        while self.is_next(&Token::Or) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.unary()?;
+        while self.is_next(&Token::And) {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, FilterError> {
+        if self.is_next(&Token::Not) {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+
+        match self.next()? {
+            (Token::LParen, _) => {
+                let expr = self.or()?;
+                match self.next()? {
+                    (Token::RParen, _) => Ok(expr),
+                    (_, position) => Err(FilterError::UnexpectedToken(position)),
+                }
+            }
+            (Token::Ident(ident), _) => self.attribute(ident),
+            (_, position) => Err(FilterError::UnexpectedToken(position)),
+        }
+    }
+
+    fn attribute(&mut self, ident: String) -> Result<Expr, FilterError> {
+        if let Some((_, attribute)) = BOOL_ATTRIBUTES.iter().find(|(n, _)| *n == ident) {
+            return Ok(Expr::Bool(*attribute));
+        }
+
+        let number_attribute = NUMBER_ATTRIBUTES.iter().find(|(n, _)| *n == ident);
+        let string_attribute = STRING_ATTRIBUTES.iter().find(|(n, _)| *n == ident);
+        if number_attribute.is_none() && string_attribute.is_none() {
+            return Err(FilterError::UnknownAttribute(ident));
+        }
+
+        let operator = match self.next()? {
+            (Token::Operator(operator), _) => operator,
+            (_, position) => return Err(FilterError::UnexpectedToken(position)),
+        };
+        let invalid_operator = || FilterError::InvalidOperator(ident.clone(), operator.as_str());
+
+        if let Some((_, attribute)) = number_attribute {
+            if operator == Operator::Match {
+                return Err(invalid_operator());
+            }
+            return match self.next()? {
+                (Token::Number(Literal::Percent(_)), _)
+                    if !matches!(
+                        attribute,
+                        NumberAttribute::Size
+                            | NumberAttribute::CumSum
+                            | NumberAttribute::Exclusive
+                    ) =>
+                {
+                    Err(FilterError::InvalidPercentage(ident))
+                }
+                (Token::Number(literal), _) => Ok(Expr::Number(*attribute, operator, literal)),
+                (_, position) => Err(FilterError::UnexpectedToken(position)),
+            };
+        }
+
+        let (_, attribute) = string_attribute.unwrap();
+        let literal = match self.next()? {
+            (Token::String(literal), _) => literal,
+            (_, position) => return Err(FilterError::UnexpectedToken(position)),
+        };
+        let (pattern, is_negated) = match operator {
+            Operator::Eq => (Pattern::Equal(literal), false),
+            // For `features`, `!=` means that no feature is equal.
+            Operator::Ne => (Pattern::Equal(literal), true),
+            #[cfg(feature = "regex")]
+            Operator::Match => (Pattern::Regex(regex_lite::Regex::new(&literal)?), false),
+            #[cfg(not(feature = "regex"))]
+            Operator::Match => (Pattern::Prefix(literal), false),
+            _ => return Err(invalid_operator()),
+        };
+        Ok(Expr::String(*attribute, pattern, is_negated))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{GraphBuilder, NodeId, Source};
+
+    /// `app` depends on `serde`, `serde_json` and `tokio2`, and `serde_json` depends on `serde`.
+    fn graph() -> Graph {
+        let mut builder = GraphBuilder::new();
+        let registry = || Some(Source::Registry(None));
+        let app = builder.add_node(
+            NodeId::new("app", "0.1.0", Some(Source::Path("/ws/app".to_string()))),
+            &[],
+            Some(1000),
+        );
+        let serde = builder.add_node(
+            NodeId::new("serde", "1.0.0", registry()),
+            &["std", "derive"],
+            Some(2000),
+        );
+        let serde_json = builder.add_node(
+            NodeId::new("serde_json", "1.0.0", registry()),
+            &[],
+            Some(3000),
+        );
+        let tokio = builder.add_node(NodeId::new("tokio2", "2.0.0", registry()), &[], Some(4000));
+        builder.add_edge(app, serde, &[]);
+        builder.add_edge(app, serde_json, &[]);
+        builder.add_edge(app, tokio, &[]);
+        builder.add_edge(serde_json, serde, &[]);
+        builder.set_workspace_members(&["/ws/app"]);
+        builder.build().unwrap()
+    }
+
+    fn matched(expr: &str) -> Vec<String> {
+        let graph = graph();
+        let mut names = Filter::parse(expr)
+            .unwrap()
+            .matched_indices(&graph)
+            .into_iter()
+            .map(|i| graph.node_weight(i).short().to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(matched("size >= 3000"), ["serde_json", "tokio2"]);
+        assert_eq!(matched("size == 3KB"), ["serde_json"]);
+        assert_eq!(matched("size != 3KB && size < 4000"), ["app", "serde"]);
+        assert_eq!(matched("depth > 1"), Vec::<String>::new());
+        assert_eq!(matched("rev_dep_count == 2"), ["serde"]);
+        assert_eq!(
+            matched("feature_count <= 1 && !root"),
+            ["serde_json", "tokio2"]
+        );
+    }
+
+    #[test]
+    fn percentages() {
+        assert_eq!(matched("size > 25%"), ["serde_json", "tokio2"]);
+        assert_eq!(matched("cum_sum >= 100%"), ["app"]);
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(
+            matched(r#"name == "app" || name == "serde" && size > 5000"#),
+            ["app"]
+        );
+        assert_eq!(
+            matched(r#"(name == "app" || name == "serde") && size > 1500"#),
+            ["serde"]
+        );
+        assert_eq!(matched("!root && !(size > 2500)"), ["serde"]);
+        assert_eq!(matched("!!root"), ["app"]);
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(matched(r#"name =~ "serde""#), ["serde", "serde_json"]);
+        assert_eq!(matched(r#"version == "2.0.0""#), ["tokio2"]);
+        assert_eq!(matched(r#"source == "workspace""#), ["app"]);
+        assert_eq!(matched(r#"features == "derive""#), ["serde"]);
+        assert_eq!(
+            matched(r#"features != "std" && !root"#),
+            ["serde_json", "tokio2"]
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex() {
+        assert_eq!(matched(r#"name =~ "\d$""#), ["tokio2"]);
+        assert_eq!(matched(r#"full =~ "^serde v\d""#), ["serde"]);
+    }
+
+    #[test]
+    fn booleans() {
+        assert_eq!(matched("workspace"), ["app"]);
+        assert_eq!(
+            matched("registry && depth == 1"),
+            ["serde", "serde_json", "tokio2"]
+        );
+        assert_eq!(matched("path || git || std"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn escapes() {
+        let tokens = tokenize(r#""a\"b\\c\d\n""#).unwrap();
+        assert_eq!(tokens, [(Token::String("a\"b\\c\\d\n".to_string()), 0)]);
+    }
+
+    #[test]
+    fn errors() {
+        let error = |expr: &str| Filter::parse(expr).unwrap_err();
+
+        assert!(matches!(error("size >"), FilterError::UnexpectedEnd));
+        assert!(matches!(error(r#"name == "a"#), FilterError::UnexpectedEnd));
+        assert!(matches!(
+            error("size > 1 1"),
+            FilterError::UnexpectedToken(9)
+        ));
+        assert!(matches!(error("size & 1"), FilterError::UnexpectedToken(5)));
+        assert!(matches!(error("(root"), FilterError::UnexpectedEnd));
+        assert!(matches!(error("root)"), FilterError::UnexpectedToken(4)));
+        assert!(matches!(error("foo == 1"), FilterError::UnknownAttribute(a) if a == "foo"));
+        assert!(matches!(
+            error(r#"name < "a""#),
+            FilterError::InvalidOperator(a, "<") if a == "name"
+        ));
+        assert!(matches!(error("size > 1XB"), FilterError::InvalidLiteral(l) if l == "1XB"));
+        assert!(matches!(
+            error("depth < 1%"),
+            FilterError::InvalidPercentage(a) if a == "depth"
+        ));
+    }
+}
@@ -1,16 +1,23 @@
-use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    sync::OnceLock,
+};
 
 use petgraph::{
+    algo::{dominators::simple_fast, has_path_connecting, is_cyclic_directed},
     dot::{Config, Dot},
     graph::NodeIndex,
     prelude::StableGraph,
     stable_graph::EdgeReference,
-    visit::{Bfs, Dfs, EdgeRef, Topo, Walker},
+    visit::{Bfs, Dfs, EdgeRef, IntoEdgeReferences, Reversed, Topo, Walker},
 };
 
+use serde::{Deserialize, Serialize, de, ser::SerializeStruct};
+
 use crate::{
-    cargo::{get_dep_graph, get_size_map},
-    coloring::{Gradient, Values},
+    cargo::{Workspace, get_dep_graph, get_size_map, get_workspace},
+    coloring::{Gradient, NodeColoringValues, Values},
+    error::{GraphError, GroupError},
     template::Templating,
 };
 
@@ -18,19 +25,25 @@ use crate::{
 /// represents a crate, and each directed edge represents a binary relation of dependency of the
 /// source node on the target node.
 ///
-/// It also keeps the size information of each crate as parsed from `cargo-bloat` output in a
-/// map, which can be accessed using the [`size`](Self::size) method for a given node index.
+/// It also keeps the size information of each crate as parsed from `cargo-bloat` output, which
+/// can be accessed using the [`size`](Self::size) method for a given node index.
 ///
 /// The node indices can be iterated using the [`node_indices`](Self::node_indices) method, though
 /// there is **no** guarantee of the order of iteration. Use [`dfs`](Self::dfs), [`bfs`](Self::bfs),
 /// or [`topo`](Self::topo) if the order of iteration is important, e.g. when a crate must be
 /// traversed before its dependencies.
 ///
-/// While [Graph] can be mutated, it is deliberately limited to only
-/// [`change_root`](Self::change_root), [`remove_deep_deps`](Self::remove_deep_deps) and
-/// [`remove_indices`](Self::remove_indices), as the graph structure should only be reduced and not
-/// expanded. In addition, any remaining non-reachable node from the root after these operations
-/// will also be removed.
+/// While [Graph] can be mutated, it is deliberately limited to
+/// [`change_root`](Self::change_root), [`remove_deep_deps`](Self::remove_deep_deps),
+/// [`remove_indices`](Self::remove_indices), [`retain_paths_to`](Self::retain_paths_to) and
+/// [`retain_top`](Self::retain_top), which only remove nodes, as well as
+/// [`contract_indices`](Self::contract_indices), [`focus`](Self::focus) and
+/// [`group`](Self::group), which may also add edges, ellipsis nodes or group nodes in place of
+/// removed ones, but never add crates. In addition, any node that is not reachable from the root
+/// after these operations is removed.
+///
+/// To try out different filters from one analysis without mutating it, use [`view`](Self::view)
+/// or [`subgraph`](Self::subgraph) to create a filtered copy instead.
 ///
 /// # Examples
 ///
@@ -68,12 +81,13 @@ use crate::{
 /// }
 /// ```
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Graph {
     inner: StableGraph<NodeWeight, EdgeWeight>,
-    size_map: HashMap<String, usize>,
+    sizes: Vec<Option<usize>>,
     std: Option<NodeIndex>,
     root: NodeIndex,
+    exclusive_sizes: OnceLock<Vec<usize>>,
 }
 
 impl Graph {
@@ -87,16 +101,20 @@ impl Graph {
     ///   `cargo tree --edges=no-build,no-proc-macro,no-dev,features --prefix=depth --color=never ...`
     /// * `cargo_bloat_output` should be the output of
     ///   `cargo bloat -n0 --message-format=json --crates ...`
+    /// * `cargo_metadata_output` should be the output of
+    ///   `cargo metadata --format-version=1 --no-deps ...`
     ///
     /// # Panics
     /// May panic if the cargo outputs are malformed.
     pub fn new(
         cargo_tree_output: &str,
         cargo_bloat_output: &str,
+        cargo_metadata_output: &str,
         std: bool,
         bin: Option<&str>,
     ) -> Self {
         let mut inner = get_dep_graph(cargo_tree_output);
+        apply_workspace(&mut inner, &get_workspace(cargo_metadata_output));
         let mut size_map = get_size_map(cargo_bloat_output);
         if let Some(bin) = bin {
             let size = size_map.get(bin).copied().unwrap_or_default();
@@ -107,23 +125,95 @@ impl Graph {
                 .to_string();
             *size_map.entry(root_name).or_default() += size;
         }
-        let std = std.then(|| {
-            let weight = NodeWeight {
-                name: "std ".to_string(),
-                short_end: 3,
-                features: BTreeMap::new(),
-            };
-            inner.add_node(weight)
-        });
+        let std = std.then(|| inner.add_node(NodeWeight::synthetic("std")));
         inner.shrink_to_fit();
-        let mut graph = Graph {
+        let sizes = normalize_sizes(inner.node_weights(), &size_map);
+        Graph {
             inner,
-            size_map,
+            sizes,
             std,
             root: NodeIndex::new(0),
-        };
-        graph.normalize_sizes();
-        graph
+            exclusive_sizes: OnceLock::new(),
+        }
+    }
+
+    /// Create a new graph from nodes at the given indices with their sizes, and edges between them,
+    /// checking that the indices are exactly `0..nodes.len()`, and that it is a DAG where all
+    /// nodes except `std` are reachable from the root.
+    pub(crate) fn from_parts(
+        nodes: Vec<(usize, NodeWeight, Option<usize>)>,
+        edges: Vec<(usize, usize, EdgeWeight)>,
+        root: usize,
+        std: Option<usize>,
+    ) -> Result<Self, GraphError> {
+        let count = nodes.len();
+
+        let mut has_index = vec![false; count];
+        for (index, _, _) in nodes.iter() {
+            match has_index.get_mut(*index) {
+                None => return Err(GraphError::InvalidNode(*index)),
+                Some(true) => return Err(GraphError::DuplicateNode(*index)),
+                Some(has_index) => *has_index = true,
+            }
+        }
+
+        for index in edges
+            .iter()
+            .flat_map(|(source, target, _)| [*source, *target])
+            .chain(std::iter::once(root))
+            .chain(std)
+        {
+            if index >= count {
+                return Err(GraphError::MissingNode(index));
+            }
+        }
+
+        let mut weights = (0..count).map(|_| None).collect::<Vec<_>>();
+        let mut sizes = vec![None; count];
+        for (index, weight, size) in nodes {
+            weights[index] = Some(weight);
+            sizes[index] = size;
+        }
+
+        let mut inner: StableGraph<NodeWeight, EdgeWeight> =
+            StableGraph::with_capacity(count, edges.len());
+        for weight in weights.into_iter().flatten() {
+            inner.add_node(weight);
+        }
+
+        // Edges given more than once are merged with their features combined
+        for (source, target, weight) in edges {
+            let (source, target) = (NodeIndex::new(source), NodeIndex::new(target));
+            match inner.find_edge(source, target) {
+                Some(edge) => merge_features(&mut inner[edge].features, &weight.features),
+                None => {
+                    inner.add_edge(source, target, weight);
+                }
+            }
+        }
+
+        if is_cyclic_directed(&inner) {
+            return Err(GraphError::Cyclic);
+        }
+
+        let mut has_visited = vec![false; count];
+        for index in Dfs::new(&inner, NodeIndex::new(root)).iter(&inner) {
+            has_visited[index.index()] = true;
+        }
+        if let Some(index) = inner
+            .node_indices()
+            .find(|i| !has_visited[i.index()] && Some(i.index()) != std)
+        {
+            return Err(GraphError::Unreachable(index.index()));
+        }
+
+        Ok(Graph {
+            inner,
+            sizes,
+            std: std.map(NodeIndex::new),
+            root: NodeIndex::new(root),
+            exclusive_sizes: OnceLock::new(),
+        })
     }
 
     /// Get the index of the `std` standalone node, if it exists.
@@ -164,19 +254,30 @@ impl Graph {
         self.inner.node_weight(NodeIndex::new(index)).unwrap()
     }
 
+    /// Get the stable identifier of the node at the given index.
+    ///
+    /// # Panics
+    /// Panics if the node does not exist in the graph.
+    pub fn node_id(&self, index: usize) -> NodeId {
+        let node = self.node_weight(index);
+        NodeId::new(node.short(), node.version(), node.source().cloned())
+    }
+
+    /// Find the index of the node with the given stable identifier, if it exists in the graph.
+    pub fn find(&self, id: &NodeId) -> Option<usize> {
+        self.inner
+            .node_indices()
+            .find(|i| self.inner[*i].has_id(id))
+            .map(|i| i.index())
+    }
+
     /// Get the weight of the edge at the given index.
     ///
     /// # Panics
     /// Panics if the source or target node, or the directed edge between them, does not exist in the graph.
     ///
-    /// ```
-    /// # use pugio_lib::graph::{EdgeWeight, Graph};
-    /// fn edge_iter(graph: &Graph) -> impl Iterator<Item = &EdgeWeight> {
-    ///     graph.node_indices().flat_map(move |i| {
-    ///         graph.neighbors(i, true).map(move |j| graph.edge_weight(i, j))
-    ///     })
-    /// }
-    /// ```
+    /// Use [`edges`](Self::edges) or [`edges_directed`](Self::edges_directed) instead to iterate
+    /// over edges along with their weights.
     pub fn edge_weight(&self, source: usize, target: usize) -> &EdgeWeight {
         self.inner
             .edge_weight(
@@ -187,33 +288,90 @@ impl Graph {
             .unwrap()
     }
 
+    /// Get an iterator over all edges of the graph as their source and target node indices and
+    /// weights.
+    ///
+    /// ```
+    /// # use pugio_lib::graph::Graph;
+    /// fn feature_edge_count(graph: &Graph) -> usize {
+    ///     graph.edges().filter(|(_, _, w)| !w.features().is_empty()).count()
+    /// }
+    /// ```
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize, &EdgeWeight)> {
+        self.inner
+            .edge_references()
+            .map(|e| (e.source().index(), e.target().index(), e.weight()))
+    }
+
+    /// Get an iterator over the edges of the given node index as their source and target node
+    /// indices and weights.
+    ///
+    /// If `outgoing` is `true`, get the outgoing edges (i.e., to dependencies),
+    /// otherwise get the incoming edges (i.e., from dependents).
+    pub fn edges_directed(
+        &self,
+        index: usize,
+        outgoing: bool,
+    ) -> impl Iterator<Item = (usize, usize, &EdgeWeight)> {
+        let direction = if outgoing {
+            petgraph::Direction::Outgoing
+        } else {
+            petgraph::Direction::Incoming
+        };
+        self.inner
+            .edges_directed(NodeIndex::new(index), direction)
+            .map(|e| (e.source().index(), e.target().index(), e.weight()))
+    }
+
     /// Get the size of the node at the given index.
     ///
-    /// Returns `None` if its name is not in the size map.
+    /// Returns `None` if its name is not in the `cargo-bloat` output, or it is synthetic without
+    /// any size, e.g. the ellipsis node.
     ///
     /// # Panics
     /// Panics if the node does not exist in the graph.
     pub fn size(&self, index: usize) -> Option<usize> {
-        let short_name = self
-            .inner
-            .node_weight(NodeIndex::new(index))
-            .unwrap()
-            .short();
-        self.size_map.get(short_name).copied()
+        assert!(self.inner.contains_node(NodeIndex::new(index)));
+        self.sizes[index]
+    }
+
+    /// Get the total size of all nodes in the graph, including the `std` standalone node.
+    pub fn total_size(&self) -> usize {
+        self.node_indices()
+            .map(|i| self.size(i).unwrap_or_default())
+            .sum()
+    }
+
+    /// Get the exclusive size of the node at the given index.
+    ///
+    /// This is the total size of the node and all nodes that are only reachable from the root
+    /// through it, i.e. the size that would be removed along with it. It is computed from the
+    /// dominator tree of the graph from the root, and cached until the graph is next mutated.
+    ///
+    /// # Panics
+    /// Panics if the node does not exist in the graph.
+    pub fn exclusive_size(&self, index: usize) -> usize {
+        assert!(self.inner.contains_node(NodeIndex::new(index)));
+        self.exclusive_sizes
+            .get_or_init(|| self.compute_exclusive_sizes())[index]
     }
 
-    fn normalize_sizes(&mut self) {
+    fn compute_exclusive_sizes(&self) -> Vec<usize> {
         let inner = &self.inner;
+        let dominators = simple_fast(inner, self.root);
 
-        let mut counts = HashMap::with_capacity(inner.node_count());
-        for node in inner.node_weights() {
-            *counts.entry(node.short()).or_default() += 1;
-        }
+        let mut values = vec![0; inner.capacity().0];
+        let nodes = Topo::new(inner).iter(inner).collect::<Vec<_>>();
 
-        for (name, size) in self.size_map.iter_mut() {
-            let count = counts.get(name.as_str()).copied().unwrap_or(1);
-            *size /= count;
+        // A node is always topologically sorted after its immediate dominator
+        for node in nodes.iter().rev() {
+            values[node.index()] += self.size(node.index()).unwrap_or_default();
+            if let Some(dominator) = dominators.immediate_dominator(*node) {
+                values[dominator.index()] += values[node.index()];
+            }
         }
+
+        values
     }
 
     fn node_classes(&self, is_dir_down: bool) -> Vec<Vec<usize>> {
@@ -284,28 +442,259 @@ impl Graph {
             .map(|i| i.index())
     }
 
-    /// Remove all nodes that are deeper than `max_depth` from the root, and any nodes that
-    /// are subsequently not reachable from the root.
-    pub fn remove_deep_deps(&mut self, max_depth: usize) {
-        let inner = &mut self.inner;
+    /// Get the node indices of the crates with more than one node in the graph, e.g. with multiple
+    /// versions or sources, by their short names.
+    ///
+    /// As `cargo-bloat` reports sizes by short names, the size of such a crate is divided equally
+    /// among its nodes, hence its combined size is the sum of their sizes.
+    ///
+    /// ```
+    /// # use pugio_lib::graph::Graph;
+    /// fn print_duplicates(graph: &Graph) {
+    ///     for (name, indices) in graph.duplicates() {
+    ///         let size: usize = indices.iter().map(|i| graph.size(*i).unwrap_or_default()).sum();
+    ///         println!("{name}: {} versions, {size} bytes", indices.len());
+    ///     }
+    /// }
+    /// ```
+    pub fn duplicates(&self) -> BTreeMap<&str, Vec<usize>> {
+        let mut map: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        for index in self.inner.node_indices() {
+            map.entry(self.inner[index].short())
+                .or_default()
+                .push(index.index());
+        }
 
-        // TODO: use petgraph#868 once merged
-        let mut queue = VecDeque::from([(self.root, 0)]);
-        let mut has_visited = vec![false; inner.capacity().0];
-        has_visited[self.root.index()] = true;
+        map.retain(|_, indices| indices.len() > 1);
+        map
+    }
+
+    /// Get up to `max_paths` dependency paths from the root to the node at the given index, and the
+    /// total number of such paths.
+    ///
+    /// Each path starts with the root and ends with the given node. The total number of paths
+    /// saturates at [`usize::MAX`], as it grows exponentially with the depth of the graph.
+    ///
+    /// # Panics
+    /// Panics if the node does not exist in the graph.
+    ///
+    /// ```
+    /// # use pugio_lib::graph::Graph;
+    /// fn print_paths(graph: &Graph, index: usize) {
+    ///     let (paths, count) = graph.paths_to(index, 8);
+    ///     println!("{count} paths, showing {}", paths.len());
+    ///
+    ///     for path in paths {
+    ///         let names = path.iter().map(|i| graph.node_weight(*i).short()).collect::<Vec<_>>();
+    ///         println!("{}", names.join(" -> "));
+    ///     }
+    /// }
+    /// ```
+    pub fn paths_to(&self, index: usize, max_paths: usize) -> (Vec<Vec<usize>>, usize) {
+        let inner = &self.inner;
+        let target = NodeIndex::new(index);
+        assert!(inner.contains_node(target));
+
+        let mut is_ancestor = vec![false; inner.capacity().0];
+        is_ancestor[index] = true;
+        for node in Bfs::new(Reversed(inner), target).iter(Reversed(inner)) {
+            is_ancestor[node.index()] = true;
+        }
+
+        let mut paths = Vec::new();
+        let mut path = Vec::new();
+        let mut stack = if is_ancestor[self.root.index()] {
+            Vec::from([(self.root, 0)])
+        } else {
+            Vec::new()
+        };
 
-        while let Some((node, depth)) = queue.pop_front()
-            && depth < max_depth
+        // Depth-first search restricted to ancestors of the target, where each node is pushed with
+        // the length of the path leading to it
+        while let Some((node, len)) = stack.pop()
+            && paths.len() < max_paths
         {
-            for target in inner.neighbors(node) {
-                if !has_visited[target.index()] {
-                    queue.push_back((target, depth + 1));
-                    has_visited[target.index()] = true;
+            path.truncate(len);
+            path.push(node.index());
+
+            if node == target {
+                paths.push(path.clone());
+                continue;
+            }
+
+            for next in inner.neighbors(node) {
+                if is_ancestor[next.index()] {
+                    stack.push((next, len + 1));
                 }
             }
         }
 
+        (paths, self.path_counts()[index])
+    }
+
+    /// Get the number of paths from the root to each node by index, saturating at [`usize::MAX`].
+    pub(crate) fn path_counts(&self) -> Vec<usize> {
+        let inner = &self.inner;
+        let mut counts = vec![0_usize; inner.capacity().0];
+        counts[self.root.index()] = 1;
+        for node in Topo::new(inner).iter(inner) {
+            for next in inner.neighbors(node) {
+                counts[next.index()] = counts[next.index()].saturating_add(counts[node.index()]);
+            }
+        }
+
+        counts
+    }
+
+    /// Get the path from the root carrying the most bytes, i.e. with the largest sum of the sizes of
+    /// its nodes, starting with the root and ending with a node without dependencies.
+    ///
+    /// ```
+    /// # use pugio_lib::graph::Graph;
+    /// fn print_heaviest_path(graph: &Graph) {
+    ///     let names = graph
+    ///         .heaviest_path()
+    ///         .into_iter()
+    ///         .map(|i| graph.node_weight(i).short())
+    ///         .collect::<Vec<_>>();
+    ///     println!("{}", names.join(" -> "));
+    /// }
+    /// ```
+    pub fn heaviest_path(&self) -> Vec<usize> {
+        self.max_path(|i| self.size(i).unwrap_or_default())
+    }
+
+    /// Get the longest dependency chain from the root, i.e. with the most nodes, starting with the
+    /// root and ending with a node without dependencies.
+    pub fn longest_path(&self) -> Vec<usize> {
+        self.max_path(|_| 1)
+    }
+
+    /// Get the path from the root with the largest sum of the given node weights, with ties broken
+    /// by node index.
+    fn max_path(&self, weight: impl Fn(usize) -> usize) -> Vec<usize> {
+        let mut sums = vec![0; self.node_capacity()];
+        let mut nexts = vec![None; self.node_capacity()];
+
+        let nodes: Vec<usize> = self.topo().collect();
+
+        for node in nodes.iter().rev() {
+            let next = self
+                .neighbors(*node, true)
+                .max_by_key(|t| (sums[*t], std::cmp::Reverse(*t)));
+            sums[*node] = weight(*node) + next.map(|t| sums[t]).unwrap_or_default();
+            nexts[*node] = next;
+        }
+
+        let mut path = vec![self.root.index()];
+        while let Some(next) = nexts[*path.last().unwrap()] {
+            path.push(next);
+        }
+        path
+    }
+
+    /// Remove all nodes that are deeper than `max_depth` from the root, and any nodes that
+    /// are subsequently not reachable from the root.
+    pub fn remove_deep_deps(&mut self, max_depth: usize) {
+        let inner = &mut self.inner;
+
+        let has_visited =
+            visit_within(inner, [self.root], max_depth, petgraph::Direction::Outgoing);
+
         remove_not_visited(inner, &has_visited, self.std);
+        self.exclusive_sizes.take();
+    }
+
+    /// Retain only the nodes at the given indices, the nodes within `up` hops upwards (dependents)
+    /// and `down` hops downwards (dependencies) from them, and return the index of the ellipsis
+    /// node standing in for the removed nodes above them, if any.
+    ///
+    /// If the root node is removed, the ellipsis node becomes the new root. Otherwise, it is added
+    /// as a dependency of the root if some retained nodes are no longer reachable from it. In both
+    /// cases, it is a dependent of all retained nodes that have lost all of their dependents. See
+    /// [`NodeWeight::is_ellipsis`].
+    ///
+    /// ```
+    /// # use pugio_lib::graph::Graph;
+    /// fn focus_tokio(graph: &mut Graph) {
+    ///     let iter = graph.node_indices().filter(|i| {
+    ///        graph.node_weight(*i).short() == "tokio"
+    ///     }).collect::<Vec<_>>().into_iter();
+    ///
+    ///     graph.focus(iter, 2, 3);
+    /// }
+    /// ```
+    pub fn focus(
+        &mut self,
+        indices: impl Iterator<Item = usize>,
+        up: usize,
+        down: usize,
+    ) -> Option<usize> {
+        let inner = &self.inner;
+
+        let starts = indices
+            .map(NodeIndex::new)
+            .filter(|i| inner.contains_node(*i))
+            .collect::<Vec<_>>();
+
+        let mut has_visited = visit_within(
+            inner,
+            starts.iter().copied(),
+            up,
+            petgraph::Direction::Incoming,
+        );
+        let downwards = visit_within(inner, starts, down, petgraph::Direction::Outgoing);
+        for (is_upward, is_downward) in has_visited.iter_mut().zip(downwards) {
+            *is_upward |= is_downward;
+        }
+
+        let is_root_retained = has_visited[self.root.index()];
+        remove_not_visited(&mut self.inner, &has_visited, self.std);
+
+        let inner = &self.inner;
+        let orphans = inner
+            .node_indices()
+            .filter(|i| {
+                *i != self.root
+                    && Some(*i) != self.std
+                    && inner
+                        .neighbors_directed(*i, petgraph::Direction::Incoming)
+                        .next()
+                        .is_none()
+            })
+            .collect::<Vec<_>>();
+
+        self.exclusive_sizes.take();
+
+        if is_root_retained && orphans.is_empty() {
+            return None;
+        }
+
+        let ellipsis = self.add_node(NodeWeight::synthetic(ELLIPSIS), None);
+
+        if is_root_retained {
+            self.add_edge(
+                self.root.index(),
+                ellipsis,
+                EdgeWeight {
+                    features: BTreeMap::new(),
+                },
+            );
+        } else {
+            self.root = NodeIndex::new(ellipsis);
+        }
+
+        for orphan in orphans {
+            self.add_edge(
+                ellipsis,
+                orphan.index(),
+                EdgeWeight {
+                    features: BTreeMap::new(),
+                },
+            );
+        }
+
+        Some(ellipsis)
     }
 
     fn remove_unreachable(&mut self) {
@@ -316,6 +705,7 @@ impl Graph {
         }
 
         remove_not_visited(&mut self.inner, &has_visited, self.std);
+        self.exclusive_sizes.take();
     }
 
     /// Remove the nodes at the given indices, and any nodes that are subsequently not reachable
@@ -330,86 +720,452 @@ impl Graph {
         self.remove_unreachable();
     }
 
-    /// Change the root node to the given index, and remove any nodes that are not reachable from
-    /// the new root.
-    pub fn change_root(&mut self, new_root_index: usize) {
-        let index = NodeIndex::new(new_root_index);
-        assert!(self.inner.contains_node(index));
-        self.root = index;
+    /// Contract the nodes at the given indices, i.e. remove each node while connecting all of its
+    /// dependents directly to all of its dependencies, so that they remain reachable.
+    ///
+    /// The root node cannot be contracted and is skipped if given. A newly added edge has no
+    /// features.
+    pub fn contract_indices(&mut self, indices: impl Iterator<Item = usize>) {
+        let inner = &mut self.inner;
+
+        for index in indices.map(NodeIndex::new) {
+            if index == self.root || !inner.contains_node(index) {
+                continue;
+            }
+
+            let sources = inner
+                .neighbors_directed(index, petgraph::Direction::Incoming)
+                .collect::<Vec<_>>();
+            let targets = inner.neighbors(index).collect::<Vec<_>>();
+
+            for source in sources.iter() {
+                for target in targets.iter() {
+                    if inner.find_edge(*source, *target).is_none() {
+                        inner.add_edge(
+                            *source,
+                            *target,
+                            EdgeWeight {
+                                features: BTreeMap::new(),
+                            },
+                        );
+                    }
+                }
+            }
+
+            inner.remove_node(index);
+        }
+
         self.remove_unreachable();
     }
 
-    /// Output the graph in DOT format with the given options, templating, coloring values, and
-    /// gradient.
-    ///
-    /// The `values` parameter should have been created from this graph, possibly before any
-    /// node removals.
+    /// Retain only the root, the nodes at the given indices, and the nodes that lie on any path
+    /// from the root to them, removing all other nodes. Nonexistent indices are skipped.
     ///
     /// ```
     /// # use pugio_lib::graph::Graph;
-    /// use pugio_lib::template::{Template, Templating};
-    /// use pugio_lib::coloring::{Gradient, Values, NodeColoringScheme, NodeColoringGradient, NodeColoringValues};
-    ///
-    /// fn output(graph: &Graph) -> String {
-    ///     let template_options = Default::default();
-    ///     let template = Template::new(&template_options).unwrap();
-    ///     let values = Some(NodeColoringValues::new(graph, NodeColoringScheme::CumSum));
-    ///     let gradient = NodeColoringGradient::Viridis;
+    /// fn retain_tokio(graph: &mut Graph) {
+    ///     let iter = graph.node_indices().filter(|i| {
+    ///        graph.node_weight(*i).short().starts_with("tokio")
+    ///     }).collect::<Vec<_>>().into_iter();
     ///
-    ///     graph.output_dot(&Default::default(), &template, &values, &gradient)
+    ///     graph.retain_paths_to(iter);
     /// }
-    ///
     /// ```
-    pub fn output_dot<C, V, T, R, S, G>(
-        &self,
-        dot_options: &DotOptions,
-        templating: &R,
-        values: &S,
-        gradient: &G,
-    ) -> String
-    where
-        R: Templating<Context = C, Value = V>,
-        S: Values<Context = C, Value = V, Output = T>,
-        G: Gradient<Input = T>,
-    {
-        let classes = dot_options
-            .highlight
-            .map(|is_dir_down| self.node_classes(is_dir_down));
+    pub fn retain_paths_to(&mut self, indices: impl Iterator<Item = usize>) {
+        let inner = &mut self.inner;
 
-        let node_binding = |_, (i, _): (NodeIndex, _)| {
-            let index = i.index();
-            let size = self.size(index).unwrap_or_default();
-            let width = (size as f32 / 4096.0 + 1.0).log10();
+        let mut has_visited = vec![false; inner.capacity().0];
+        has_visited[self.root.index()] = true;
 
-            let context = values.context();
-            let value = values.value(index);
-            let output = values.output(index);
-            let color = gradient.color(output, dot_options.dark_mode, dot_options.inverse_gradient);
-            let color = format!("#{color:X}");
+        let mut stack = indices
+            .map(NodeIndex::new)
+            .filter(|i| inner.contains_node(*i))
+            .collect::<Vec<_>>();
+        for node in stack.iter() {
+            has_visited[node.index()] = true;
+        }
 
-            let (label, tooltip) = templating.node(self, index, value, context);
+        while let Some(node) = stack.pop() {
+            for source in inner.neighbors_directed(node, petgraph::Direction::Incoming) {
+                if !has_visited[source.index()] {
+                    stack.push(source);
+                    has_visited[source.index()] = true;
+                }
+            }
+        }
 
-            let classes = if let Some(classes) = &classes {
-                &classes[index]
-                    .iter()
-                    .map(|i| format!("node{i}"))
-                    .collect::<Vec<_>>()
-                    .join(" ")
-            } else {
-                ""
-            };
+        remove_not_visited(inner, &has_visited, self.std);
+        self.exclusive_sizes.take();
+    }
 
-            format!(
-                r#"class = "{classes}" label = "{label}" tooltip = "{tooltip}" width = {width} fillcolor= "{color}""#,
-            )
-        };
+    /// Retain only the `n` nodes with the largest values, other than the root and the `std`
+    /// standalone node, and the nodes that lie on any path from the root to them, removing all
+    /// other nodes.
+    ///
+    /// The `values` parameter should have been created from this graph, e.g. with
+    /// [`NodeColoringScheme::CumSum`](crate::coloring::NodeColoringScheme::CumSum), possibly before
+    /// any node removals. Ties are broken by node index.
+    ///
+    /// ```
+    /// # use pugio_lib::{coloring::{NodeColoringScheme, NodeColoringValues}, graph::Graph};
+    /// fn retain_top_ten(graph: &mut Graph) {
+    ///     let values =
+    ///         NodeColoringValues::new(graph, NodeColoringScheme::Exclusive, Default::default());
+    ///     graph.retain_top(10, &values);
+    /// }
+    /// ```
+    pub fn retain_top(&mut self, n: usize, values: &NodeColoringValues) {
+        let mut indices_values = values
+            .indices_values()
+            .filter(|(i, _)| {
+                let index = NodeIndex::new(*i);
+                index != self.root && Some(index) != self.std && self.inner.contains_node(index)
+            })
+            .collect::<Vec<_>>();
+        indices_values.sort_by_key(|(i, v)| (std::cmp::Reverse(*v), *i));
 
-        let edge_binding = |_, e: EdgeReference<'_, EdgeWeight>| {
-            let (label, tooltip) = templating.edge(self, e.source().index(), e.target().index());
+        self.retain_paths_to(indices_values.into_iter().take(n).map(|(i, _)| i));
+    }
 
-            let classes = if let Some(classes) = &classes {
-                let i = if dot_options.highlight.unwrap() {
-                    e.source()
+    /// Group the nodes at the given indices into a single node with the given name, and return its
+    /// index.
+    ///
+    /// The size of the group node is the sum of the sizes of its members, its edges are the union
+    /// of the edges of its members to and from non-members, and its members are listed by their
+    /// full names in [`NodeWeight::members`]. If the root node is a member, the group node becomes
+    /// the new root. The `std` standalone node cannot be grouped and is skipped if given.
+    ///
+    /// # Errors
+    /// Returns an error if there are no members, if the name is the short name of a non-member, as
+    /// they would be indistinguishable, or if grouping would create a cycle, i.e. a member depends
+    /// on another member through a non-member.
+    ///
+    /// ```
+    /// # use pugio_lib::graph::Graph;
+    /// fn group_windows(graph: &mut Graph) {
+    ///     let iter = graph.node_indices().filter(|i| {
+    ///        graph.node_weight(*i).short().starts_with("windows")
+    ///     }).collect::<Vec<_>>().into_iter();
+    ///
+    ///     let _ = graph.group("windows-*", iter);
+    /// }
+    /// ```
+    pub fn group(
+        &mut self,
+        name: &str,
+        indices: impl Iterator<Item = usize>,
+    ) -> Result<usize, GroupError> {
+        let inner = &self.inner;
+
+        let mut is_member = vec![false; inner.capacity().0];
+        let mut members = Vec::new();
+        for index in indices.map(NodeIndex::new) {
+            if inner.contains_node(index) && Some(index) != self.std && !is_member[index.index()] {
+                is_member[index.index()] = true;
+                members.push(index);
+            }
+        }
+
+        if members.is_empty() {
+            return Err(GroupError::Empty(name.to_string()));
+        }
+
+        if inner
+            .node_indices()
+            .any(|i| !is_member[i.index()] && inner[i].short() == name)
+        {
+            return Err(GroupError::Conflict(name.to_string()));
+        }
+
+        let mut has_visited = vec![false; inner.capacity().0];
+        let mut stack = members
+            .iter()
+            .flat_map(|m| inner.neighbors(*m))
+            .filter(|t| !is_member[t.index()])
+            .collect::<Vec<_>>();
+
+        while let Some(node) = stack.pop() {
+            if has_visited[node.index()] {
+                continue;
+            }
+            has_visited[node.index()] = true;
+
+            for target in inner.neighbors(node) {
+                if is_member[target.index()] {
+                    return Err(GroupError::Cycle(name.to_string()));
+                }
+                stack.push(target);
+            }
+        }
+
+        let size = members
+            .iter()
+            .map(|m| self.size(m.index()).unwrap_or_default())
+            .sum();
+
+        let mut incoming: BTreeMap<NodeIndex, BTreeMap<String, Vec<String>>> = BTreeMap::new();
+        let mut outgoing: BTreeMap<NodeIndex, BTreeMap<String, Vec<String>>> = BTreeMap::new();
+
+        for member in members.iter() {
+            for edge in inner.edges_directed(*member, petgraph::Direction::Incoming) {
+                if !is_member[edge.source().index()] {
+                    merge_features(
+                        incoming.entry(edge.source()).or_default(),
+                        &edge.weight().features,
+                    );
+                }
+            }
+            for edge in inner.edges(*member) {
+                if !is_member[edge.target().index()] {
+                    merge_features(
+                        outgoing.entry(edge.target()).or_default(),
+                        &edge.weight().features,
+                    );
+                }
+            }
+        }
+
+        let mut weight = NodeWeight::synthetic(name);
+        weight.members = members
+            .iter()
+            .map(|m| inner[*m].full().to_string())
+            .collect();
+
+        let inner = &mut self.inner;
+        let group = inner.add_node(weight);
+
+        for (source, features) in incoming {
+            inner.add_edge(source, group, EdgeWeight { features });
+        }
+        for (target, features) in outgoing {
+            inner.add_edge(group, target, EdgeWeight { features });
+        }
+
+        if is_member[self.root.index()] {
+            self.root = group;
+        }
+
+        for member in members {
+            inner.remove_node(member);
+        }
+
+        self.set_size(group.index(), Some(size));
+
+        Ok(group.index())
+    }
+
+    /// Change the root node to the given index, and remove any nodes that are not reachable from
+    /// the new root.
+    pub fn change_root(&mut self, new_root_index: usize) {
+        let index = NodeIndex::new(new_root_index);
+        assert!(self.inner.contains_node(index));
+        self.root = index;
+        self.remove_unreachable();
+    }
+
+    /// Create a new graph by applying the given filter to a copy of this graph, leaving this graph
+    /// untouched.
+    ///
+    /// The whole graph is cloned before filtering, including its sizes, hence each view costs as
+    /// much memory as the graph itself.
+    ///
+    /// Node indices are preserved in the new graph, so any values created from this graph, e.g.
+    /// [`NodeColoringValues`], remain valid for it.
+    ///
+    /// ```
+    /// # use pugio_lib::graph::Graph;
+    /// fn shallow_views(graph: &Graph) -> Vec<Graph> {
+    ///     (1..=3)
+    ///         .map(|depth| graph.view(|g| g.remove_deep_deps(depth)))
+    ///         .collect()
+    /// }
+    /// ```
+    pub fn view(&self, filter: impl FnOnce(&mut Graph)) -> Graph {
+        let mut graph = self.clone();
+        filter(&mut graph);
+        graph
+    }
+
+    /// Create a new graph with only the root and the nodes at the given indices, along with the
+    /// edges between them, and without any nodes that are subsequently not reachable from the
+    /// root, leaving this graph untouched.
+    ///
+    /// Node indices are preserved in the new graph as with [`view`](Self::view). Nonexistent
+    /// indices are skipped.
+    pub fn subgraph(&self, indices: impl Iterator<Item = usize>) -> Graph {
+        let mut is_retained = vec![false; self.node_capacity()];
+        is_retained[self.root.index()] = true;
+        for index in indices.filter(|i| self.inner.contains_node(NodeIndex::new(*i))) {
+            is_retained[index] = true;
+        }
+
+        self.view(|graph| {
+            remove_not_visited(&mut graph.inner, &is_retained, graph.std);
+            graph.remove_unreachable();
+        })
+    }
+
+    /// Get the redundant edges of the transitive reduction, i.e. the edges from a node to a
+    /// dependency that is also reachable through another of its dependencies.
+    fn redundant_edges(&self) -> HashSet<(NodeIndex, NodeIndex)> {
+        let inner = &self.inner;
+        let mut descendants = vec![Vec::new(); inner.capacity().0];
+        let mut redundant_edges = HashSet::new();
+        // A node is a descendant of the current node iff its mark equals the current generation,
+        // so that the marks need not be reset between nodes
+        let mut marks = vec![0; inner.capacity().0];
+
+        for (generation, node) in (1..).zip(self.topo().collect::<Vec<_>>().into_iter().rev()) {
+            let targets = inner.neighbors(NodeIndex::new(node)).collect::<Vec<_>>();
+            let mut node_descendants = Vec::new();
+
+            for target in targets.iter() {
+                for &descendant in descendants[target.index()].iter() {
+                    if marks[descendant] != generation {
+                        marks[descendant] = generation;
+                        node_descendants.push(descendant);
+                    }
+                }
+            }
+
+            for target in targets.iter() {
+                if marks[target.index()] == generation {
+                    redundant_edges.insert((NodeIndex::new(node), *target));
+                } else {
+                    marks[target.index()] = generation;
+                    node_descendants.push(target.index());
+                }
+            }
+
+            descendants[node] = node_descendants;
+        }
+
+        redundant_edges
+    }
+
+    /// Add a node with the given weight and size, which is not reachable from the root until an
+    /// edge is added to it.
+    pub(crate) fn add_node(&mut self, weight: NodeWeight, size: Option<usize>) -> usize {
+        let index = self.inner.add_node(weight).index();
+        self.set_size(index, size);
+        index
+    }
+
+    /// Set the size of the node at the given index, or remove it if `None`.
+    pub(crate) fn set_size(&mut self, index: usize, size: Option<usize>) {
+        if self.sizes.len() <= index {
+            self.sizes.resize(index + 1, None);
+        }
+        self.sizes[index] = size;
+        self.exclusive_sizes.take();
+    }
+
+    /// Add an edge with the given weight if it does not already exist, and it would not create a
+    /// cycle.
+    pub(crate) fn add_edge(&mut self, source: usize, target: usize, weight: EdgeWeight) {
+        let (source, target) = (NodeIndex::new(source), NodeIndex::new(target));
+        if self.inner.find_edge(source, target).is_none()
+            && !has_path_connecting(&self.inner, target, source, None)
+        {
+            self.inner.add_edge(source, target, weight);
+            self.exclusive_sizes.take();
+        }
+    }
+
+    /// Output the graph in DOT format with the given options, templating, coloring values, and
+    /// gradient.
+    ///
+    /// The `values` parameter should have been created from this graph, possibly before any
+    /// node removals.
+    ///
+    /// ```
+    /// # use pugio_lib::graph::Graph;
+    /// use pugio_lib::template::{Template, Templating};
+    /// use pugio_lib::coloring::{
+    ///     Attribution, Gradient, Values, NodeColoringScheme, NodeColoringGradient, NodeColoringValues,
+    /// };
+    ///
+    /// fn output(graph: &Graph) -> String {
+    ///     let template_options = Default::default();
+    ///     let template = Template::new(&template_options).unwrap();
+    ///     let values = Some(NodeColoringValues::new(
+    ///         graph,
+    ///         NodeColoringScheme::CumSum,
+    ///         Attribution::EqualSplit,
+    ///     ));
+    ///     let gradient = NodeColoringGradient::Viridis;
+    ///
+    ///     graph.output_dot(&Default::default(), &template, &values, &gradient)
+    /// }
+    ///
+    /// ```
+    pub fn output_dot<C, V, T, R, S, G>(
+        &self,
+        dot_options: &DotOptions,
+        templating: &R,
+        values: &S,
+        gradient: &G,
+    ) -> String
+    where
+        R: Templating<Context = C, Value = V>,
+        S: Values<Context = C, Value = V, Output = T>,
+        G: Gradient<Input = T>,
+    {
+        let classes = dot_options
+            .highlight
+            .map(|is_dir_down| self.node_classes(is_dir_down));
+
+        let redundant_edges = if dot_options.transitive_reduction.is_some() {
+            self.redundant_edges()
+        } else {
+            HashSet::new()
+        };
+
+        let node_binding = |_, (i, _): (NodeIndex, _)| {
+            let index = i.index();
+            let width = node_width(self.size(index).unwrap_or_default());
+
+            let context = values.context();
+            let value = values.value(index);
+            let output = values.output(index);
+            let color = gradient.color(output, dot_options.dark_mode, dot_options.inverse_gradient);
+            let color = format!("#{color:X}");
+
+            let (label, tooltip) = templating.node(self, index, value, context);
+
+            let classes = if let Some(classes) = &classes {
+                &classes[index]
+                    .iter()
+                    .map(|i| format!("node{i}"))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            } else {
+                ""
+            };
+
+            let attributes = dot_options
+                .node_attributes
+                .get(&index)
+                .map(String::as_str)
+                .unwrap_or_default();
+
+            let style = if self.node_weight(index).is_ellipsis() {
+                r#"style = "dashed" "#
+            } else {
+                ""
+            };
+
+            format!(
+                r#"class = "{classes}" label = "{label}" tooltip = "{tooltip}" width = {width} fillcolor= "{color}" {style}{attributes}"#,
+            )
+        };
+
+        let edge_binding = |_, e: EdgeReference<'_, EdgeWeight>| {
+            let (label, tooltip) = templating.edge(self, e.source().index(), e.target().index());
+
+            let classes = if let Some(classes) = &classes {
+                let i = if dot_options.highlight.unwrap() {
+                    e.source()
                 } else {
                     e.target()
                 };
@@ -422,22 +1178,450 @@ impl Graph {
                 ""
             };
 
+            let attributes = dot_options
+                .edge_attributes
+                .get(&(e.source().index(), e.target().index()))
+                .map(String::as_str)
+                .unwrap_or_default();
+
+            let style = if redundant_edges.contains(&(e.source(), e.target())) {
+                r#"style = "dotted" constraint = false "#
+            } else {
+                ""
+            };
+
             format!(
-                r#"class = "{classes}" label = "{label}" edgetooltip = "{tooltip}" labeltooltip = "{tooltip}""#
+                r#"class = "{classes}" label = "{label}" edgetooltip = "{tooltip}" labeltooltip = "{tooltip}" {style}{attributes}"#
             )
         };
 
+        let reduced;
+        let inner = if dot_options.transitive_reduction == Some(true) {
+            let mut graph = self.inner.clone();
+            graph.retain_edges(|g, e| {
+                let (source, target) = g.edge_endpoints(e).unwrap();
+                !redundant_edges.contains(&(source, target))
+            });
+            reduced = graph;
+            &reduced
+        } else {
+            &self.inner
+        };
+
         let dot = Dot::with_attr_getters(
-            &self.inner,
+            inner,
             &[Config::EdgeNoLabel, Config::NodeNoLabel],
             &edge_binding,
             &node_binding,
         );
 
-        format!("{dot:?}")
+        let mut dot = format!("{dot:?}");
+
+        if dot_options.cluster {
+            let mut clusters: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+            for index in self.node_indices() {
+                if let Some(source) = self.node_weight(index).source() {
+                    clusters.entry(source.kind()).or_default().push(index);
+                }
+            }
+
+            let subgraphs = clusters
+                .into_iter()
+                .map(|(kind, indices)| {
+                    let nodes = indices
+                        .iter()
+                        .map(|i| i.to_string())
+                        .collect::<Vec<_>>()
+                        .join("; ");
+                    format!(
+                        "    subgraph cluster_{kind} {{\n        label = \"{kind}\"\n        style = \"dashed\"\n        {nodes}\n    }}\n"
+                    )
+                })
+                .collect::<String>();
+
+            // ASSERT: DOT output always ends with the closing brace of the graph
+            let index = dot.rfind('}').unwrap();
+            dot.insert_str(index, &subgraphs);
+        }
+
+        dot
+    }
+}
+
+/// Builder of a [`Graph`] from nodes, edges and sizes, e.g. for tests or synthetic inputs.
+///
+/// Sizes are keyed by short names as with [`Graph::new`], hence the sizes of nodes with the same
+/// short name are summed up and then divided equally among them. Local paths of workspace members
+/// set with [`set_workspace_members`](Self::set_workspace_members) are marked as [`Source::Workspace`].
+///
+/// ```
+/// # use pugio_lib::graph::{GraphBuilder, NodeId, Source};
+/// let mut builder = GraphBuilder::new();
+/// let app_id = NodeId::new("app", "0.1.0", Some(Source::Path("/app".to_string())));
+/// let app = builder.add_node(app_id, &[], Some(4096));
+/// let serde_id = NodeId::new("serde", "1.0.0", Some(Source::Registry(None)));
+/// let serde = builder.add_node(serde_id, &["std"], Some(1024));
+/// builder.add_edge(app, serde, &[("default", "std")]);
+/// builder.set_root(app);
+/// builder.set_workspace_members(&["/app"]);
+///
+/// let graph = builder.build().unwrap();
+/// assert_eq!(graph.size(serde), Some(1024));
+/// assert_eq!(graph.node_weight(app).source().unwrap().kind(), "workspace");
+/// ```
+#[derive(Debug, Default)]
+pub struct GraphBuilder {
+    nodes: Vec<NodeWeight>,
+    edges: Vec<(usize, usize, EdgeWeight)>,
+    size_map: HashMap<String, usize>,
+    root: usize,
+    std: Option<usize>,
+    workspace: Workspace,
+}
+
+impl GraphBuilder {
+    /// Create a new empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a crate node with the given identifier, enabled features and size, and return its
+    /// index.
+    pub fn add_node(&mut self, id: NodeId, features: &[&str], size: Option<usize>) -> usize {
+        let mut extra = if id.version().is_empty() {
+            String::new()
+        } else {
+            format!("v{}", id.version())
+        };
+        match id.source() {
+            Some(Source::Registry(Some(registry))) => {
+                extra.push_str(&format!(" (registry `{registry}`)"))
+            }
+            Some(Source::Git { url, rev }) => {
+                extra.push_str(&format!(" ({url}"));
+                if let Some(rev) = rev {
+                    extra.push_str(&format!("#{rev}"));
+                }
+                extra.push(')');
+            }
+            Some(Source::Path(path) | Source::Workspace(path)) => {
+                extra.push_str(&format!(" ({path})"))
+            }
+            Some(Source::Registry(None)) | None => {}
+        }
+
+        let weight = NodeWeight {
+            name: format!("{} {extra}", id.name()),
+            short_end: id.name().len(),
+            features: features
+                .iter()
+                .map(|f| (f.to_string(), Vec::new()))
+                .collect(),
+            members: Vec::new(),
+            source: id.source().cloned(),
+        };
+        self.push_node(weight, size)
+    }
+
+    /// Add the `std` standalone node with the given size, and return its index.
+    pub fn add_std(&mut self, size: Option<usize>) -> usize {
+        let index = self.push_node(NodeWeight::synthetic("std"), size);
+        self.std = Some(index);
+        index
+    }
+
+    fn push_node(&mut self, weight: NodeWeight, size: Option<usize>) -> usize {
+        if let Some(size) = size {
+            *self.size_map.entry(weight.short().to_string()).or_default() += size;
+        }
+        self.nodes.push(weight);
+        self.nodes.len() - 1
+    }
+
+    /// Add an edge from the source node to the target node, i.e. the source depends on the target,
+    /// with the given pairs of features, where a feature of the source enables a feature of the
+    /// target. Adding the same edge again adds to its features.
+    pub fn add_edge(&mut self, source: usize, target: usize, features: &[(&str, &str)]) {
+        let mut weight = EdgeWeight {
+            features: BTreeMap::new(),
+        };
+        for (feature, target_feature) in features {
+            weight
+                .features
+                .entry(feature.to_string())
+                .or_default()
+                .push(target_feature.to_string());
+        }
+        self.edges.push((source, target, weight));
+    }
+
+    /// Set the root node to the given index, which is the first node by default.
+    pub fn set_root(&mut self, index: usize) {
+        self.root = index;
+    }
+
+    /// Set the directories of the workspace members, whose [`Source::Path`] nodes become
+    /// [`Source::Workspace`]. There are no workspace members by default.
+    pub fn set_workspace_members(&mut self, members: &[&str]) {
+        self.workspace.members = members.iter().map(|m| m.to_string()).collect();
+    }
+
+    /// Set the workspace root directory, which all local paths are made relative to. Local paths
+    /// are kept as is by default.
+    pub fn set_workspace_root(&mut self, root: &str) {
+        self.workspace.root = Some(root.to_string());
+    }
+
+    /// Build the graph, checking that it is a DAG where all nodes except `std` are reachable from
+    /// the root.
+    ///
+    /// # Errors
+    /// Returns an error if an edge or the root refers to a non-existent node, or the graph is not a
+    /// DAG with all nodes except `std` reachable from the root.
+    pub fn build(self) -> Result<Graph, GraphError> {
+        let sizes = normalize_sizes(self.nodes.iter(), &self.size_map);
+        let mut graph = Graph::from_parts(
+            self.nodes
+                .into_iter()
+                .zip(sizes)
+                .enumerate()
+                .map(|(index, (weight, size))| (index, weight, size))
+                .collect(),
+            self.edges,
+            self.root,
+            self.std,
+        )?;
+        apply_workspace(&mut graph.inner, &self.workspace);
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+impl GraphBuilder {
+    /// Build a graph of registry crates given by name, version and size, where the first is the
+    /// root depending on all others.
+    pub(crate) fn star(crates: &[(&str, &str, usize)]) -> Graph {
+        let mut builder = GraphBuilder::new();
+        for &(name, version, size) in crates {
+            let id = NodeId::new(name, version, Some(Source::Registry(None)));
+            builder.add_node(id, &[], Some(size));
+        }
+        for target in 1..crates.len() {
+            builder.add_edge(0, target, &[]);
+        }
+        builder.build().unwrap()
+    }
+}
+
+#[cfg(test)]
+impl Graph {
+    /// Get the index of the first node with the given short name.
+    pub(crate) fn index_of(&self, short: &str) -> usize {
+        self.node_indices()
+            .find(|i| self.node_weight(*i).short() == short)
+            .unwrap()
     }
 }
 
+/// Short name of the ellipsis node added by [`Graph::focus`].
+const ELLIPSIS: &str = "…";
+
+/// Current version of the serialized graph schema.
+const SCHEMA_VERSION: u32 = 1;
+
+/// Serialize the graph with a versioned schema, e.g. in JSON:
+///
+/// ```json
+/// {
+///   "version": 1,
+///   "root": 0,
+///   "std": null,
+///   "nodes": [
+///     { "index": 0, "size": 1024, "short": "a", "extra": "v1.0.0", "features": {}, "members": [], "source": { "registry": null } },
+///     { "index": 1, "size": 2048, "short": "b", "extra": "v1.0.0", "features": {}, "members": [], "source": { "registry": null } }
+///   ],
+///   "edges": [{ "source": 0, "target": 1, "features": {} }]
+/// }
+/// ```
+///
+/// Node indices are compacted to `0..node_count`, hence they are only preserved across
+/// serialization if no nodes have been removed.
+impl Serialize for Graph {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Node<'a> {
+            index: usize,
+            size: Option<usize>,
+            #[serde(flatten)]
+            weight: &'a NodeWeight,
+        }
+
+        #[derive(Serialize)]
+        struct Edge<'a> {
+            source: usize,
+            target: usize,
+            #[serde(flatten)]
+            weight: &'a EdgeWeight,
+        }
+
+        #[derive(Serialize)]
+        struct Schema<'a> {
+            version: u32,
+            root: usize,
+            std: Option<usize>,
+            nodes: Vec<Node<'a>>,
+            edges: Vec<Edge<'a>>,
+        }
+
+        let inner = &self.inner;
+
+        let mut compact = vec![0; inner.capacity().0];
+        for (compact_index, index) in inner.node_indices().enumerate() {
+            compact[index.index()] = compact_index;
+        }
+
+        let schema = Schema {
+            version: SCHEMA_VERSION,
+            root: compact[self.root.index()],
+            std: self.std.map(|i| compact[i.index()]),
+            nodes: inner
+                .node_indices()
+                .map(|i| Node {
+                    index: compact[i.index()],
+                    size: self.sizes[i.index()],
+                    weight: &inner[i],
+                })
+                .collect(),
+            edges: inner
+                .edge_references()
+                .map(|e| Edge {
+                    source: compact[e.source().index()],
+                    target: compact[e.target().index()],
+                    weight: e.weight(),
+                })
+                .collect(),
+        };
+
+        schema.serialize(serializer)
+    }
+}
+
+/// Deserialize the graph from the versioned schema, see [`Serialize`] implementation.
+///
+/// Returns an error if the version is not supported, or if the graph invariants are violated,
+/// see [`GraphError`].
+impl<'de> Deserialize<'de> for Graph {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Node {
+            index: usize,
+            #[serde(default)]
+            size: Option<usize>,
+            #[serde(flatten)]
+            weight: NodeWeight,
+        }
+
+        #[derive(Deserialize)]
+        struct Edge {
+            source: usize,
+            target: usize,
+            #[serde(flatten)]
+            weight: EdgeWeight,
+        }
+
+        #[derive(Deserialize)]
+        struct Schema {
+            version: u32,
+            root: usize,
+            std: Option<usize>,
+            nodes: Vec<Node>,
+            edges: Vec<Edge>,
+        }
+
+        let schema = Schema::deserialize(deserializer)?;
+        if schema.version != SCHEMA_VERSION {
+            return Err(de::Error::custom(GraphError::UnsupportedVersion(
+                schema.version,
+            )));
+        }
+
+        Graph::from_parts(
+            schema
+                .nodes
+                .into_iter()
+                .map(|n| (n.index, n.weight, n.size))
+                .collect(),
+            schema
+                .edges
+                .into_iter()
+                .map(|e| (e.source, e.target, e.weight))
+                .collect(),
+            schema.root,
+            schema.std,
+        )
+        .map_err(de::Error::custom)
+    }
+}
+
+/// Get the size of each node by index from the given size map keyed by short names, where the size
+/// of a name shared by multiple nodes is divided equally among them.
+fn normalize_sizes<'a>(
+    nodes: impl Iterator<Item = &'a NodeWeight>,
+    size_map: &HashMap<String, usize>,
+) -> Vec<Option<usize>> {
+    let nodes = nodes.collect::<Vec<_>>();
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for node in nodes.iter() {
+        *counts.entry(node.short()).or_default() += 1;
+    }
+
+    nodes
+        .into_iter()
+        .map(|node| {
+            size_map
+                .get(node.short())
+                .map(|size| size / counts[node.short()])
+        })
+        .collect()
+}
+
+/// Width of a node in DOT output, in inches, given its size.
+pub(crate) fn node_width(size: usize) -> f32 {
+    (size as f32 / 4096.0 + 1.0).log10()
+}
+
+/// Visit the nodes within `max_depth` hops from the start nodes in the given direction.
+fn visit_within(
+    graph: &StableGraph<NodeWeight, EdgeWeight>,
+    starts: impl IntoIterator<Item = NodeIndex>,
+    max_depth: usize,
+    direction: petgraph::Direction,
+) -> Vec<bool> {
+    let mut has_visited = vec![false; graph.capacity().0];
+
+    // TODO: use petgraph#868 once merged
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if !has_visited[start.index()] {
+            queue.push_back((start, 0));
+            has_visited[start.index()] = true;
+        }
+    }
+
+    while let Some((node, depth)) = queue.pop_front()
+        && depth < max_depth
+    {
+        for target in graph.neighbors_directed(node, direction) {
+            if !has_visited[target.index()] {
+                queue.push_back((target, depth + 1));
+                has_visited[target.index()] = true;
+            }
+        }
+    }
+
+    has_visited
+}
+
 fn remove_not_visited(
     graph: &mut StableGraph<NodeWeight, EdgeWeight>,
     has_visited: &[bool],
@@ -455,14 +1639,114 @@ fn remove_not_visited(
     }
 }
 
+/// Mark the workspace members as [`Source::Workspace`], and make all local paths relative to the
+/// workspace root, so that the same crates in different checkouts have the same [`NodeId`]s.
+fn apply_workspace(graph: &mut StableGraph<NodeWeight, EdgeWeight>, workspace: &Workspace) {
+    for node in graph.node_weights_mut() {
+        let Some(Source::Path(path)) = &node.source else {
+            continue;
+        };
+        let is_member = workspace.members.contains(path);
+        let path = match &workspace.root {
+            Some(root) => relative_path(path, root),
+            None => path.clone(),
+        };
+        node.source = Some(if is_member {
+            Source::Workspace(path)
+        } else {
+            Source::Path(path)
+        });
+    }
+}
+
+/// Get the path relative to the base directory, or the path itself if they share no common prefix.
+fn relative_path(path: &str, base: &str) -> String {
+    let (path, base) = (std::path::Path::new(path), std::path::Path::new(base));
+    let common = path
+        .components()
+        .zip(base.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if common == 0 {
+        return path.to_string_lossy().into_owned();
+    }
+
+    let mut relative = std::path::PathBuf::new();
+    for _ in base.components().skip(common) {
+        relative.push("..");
+    }
+    for component in path.components().skip(common) {
+        relative.push(component);
+    }
+
+    if relative.as_os_str().is_empty() {
+        ".".to_string()
+    } else {
+        relative.to_string_lossy().into_owned()
+    }
+}
+
+fn merge_features(
+    features: &mut BTreeMap<String, Vec<String>>,
+    other: &BTreeMap<String, Vec<String>>,
+) {
+    for (feature, sub_features) in other {
+        let entry = features.entry(feature.clone()).or_default();
+        for sub_feature in sub_features {
+            if !entry.contains(sub_feature) {
+                entry.push(sub_feature.clone());
+            }
+        }
+    }
+}
+
 /// The weight of a node in the dependency graph, representing a crate.
 ///
 /// The crate name already has the hyphen `-` replaced with `_` as used in code.
-#[derive(Clone)]
+#[derive(Clone, Deserialize)]
+#[serde(from = "NodeWeightSchema")]
 pub struct NodeWeight {
     name: String,
     short_end: usize,
     pub(crate) features: BTreeMap<String, Vec<String>>,
+    pub(crate) members: Vec<String>,
+    source: Option<Source>,
+}
+
+#[derive(Deserialize)]
+struct NodeWeightSchema {
+    short: String,
+    extra: String,
+    #[serde(default)]
+    features: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    members: Vec<String>,
+    #[serde(default)]
+    source: Option<Source>,
+}
+
+impl From<NodeWeightSchema> for NodeWeight {
+    fn from(value: NodeWeightSchema) -> Self {
+        Self {
+            name: format!("{} {}", value.short, value.extra),
+            short_end: value.short.len(),
+            features: value.features,
+            members: value.members,
+            source: value.source,
+        }
+    }
+}
+
+impl Serialize for NodeWeight {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("NodeWeight", 5)?;
+        state.serialize_field("short", self.short())?;
+        state.serialize_field("extra", self.extra())?;
+        state.serialize_field("features", &self.features)?;
+        state.serialize_field("members", &self.members)?;
+        state.serialize_field("source", &self.source)?;
+        state.end()
+    }
 }
 
 impl std::fmt::Debug for NodeWeight {
@@ -470,6 +1754,8 @@ impl std::fmt::Debug for NodeWeight {
         f.debug_struct("NodeWeight")
             .field("name", &self.name)
             .field("features", &self.features)
+            .field("members", &self.members)
+            .field("source", &self.source)
             .finish()
     }
 }
@@ -480,13 +1766,44 @@ impl NodeWeight {
         short_end: usize,
         features: BTreeMap<String, Vec<String>>,
     ) -> Self {
+        let source = Source::parse(&name[short_end + 1..]);
         Self {
             name,
             short_end,
             features,
+            members: Vec::new(),
+            source: Some(source),
+        }
+    }
+
+    /// Create a node not parsed from `cargo-tree` output with the given short name, and without
+    /// extra information or source, e.g. the `std` standalone node.
+    pub(crate) fn synthetic(short: &str) -> Self {
+        Self {
+            name: format!("{short} "),
+            short_end: short.len(),
+            features: BTreeMap::new(),
+            members: Vec::new(),
+            source: None,
         }
     }
 
+    /// Version of the crate without the leading `v`, or empty if unknown.
+    fn version(&self) -> &str {
+        let version = self.extra().split(' ').next().unwrap_or_default();
+        version.strip_prefix('v').unwrap_or(version)
+    }
+
+    /// Check if the node has the given identifier, i.e. [`Graph::node_id`] would be equal to it,
+    /// without creating one.
+    fn has_id(&self, id: &NodeId) -> bool {
+        let is_same_source = match (&self.source, id.source()) {
+            (Some(Source::Workspace(path)), Some(Source::Path(other))) => path == other,
+            (source, other) => source.as_ref() == other,
+        };
+        self.short() == id.name() && self.version() == id.version() && is_same_source
+    }
+
     /// Short name of the crate.
     ///
     /// For example, if the full name is `pugio_lib v1.0.0`, this returns `pugio_lib`.
@@ -531,12 +1848,150 @@ impl NodeWeight {
     pub fn features(&self) -> &BTreeMap<String, Vec<String>> {
         &self.features
     }
+
+    /// Get the full names of the member crates if this is a group node created by
+    /// [`Graph::group`], otherwise an empty slice.
+    pub fn members(&self) -> &[String] {
+        &self.members
+    }
+
+    /// Whether this is the ellipsis node added by [`Graph::focus`], standing in for removed
+    /// nodes.
+    pub fn is_ellipsis(&self) -> bool {
+        self.short() == ELLIPSIS
+    }
+
+    /// Get the source of the crate.
+    ///
+    /// Returns `None` for the `std` standalone node and group nodes.
+    pub fn source(&self) -> Option<&Source> {
+        self.source.as_ref()
+    }
+}
+
+/// A stable identifier of a crate based on its package ID, i.e. its name, version and source.
+///
+/// Unlike node indices, which are only meaningful for one [`Graph`], it identifies the same
+/// crate across graphs, e.g. of different builds. Use [`Graph::node_id`] and [`Graph::find`] to
+/// convert between the two.
+///
+/// As whether a local path is a workspace member depends on the workspace, workspace members are
+/// identified by their paths as [`Source::Path`]. Local paths of graphs created by [`Graph::new`]
+/// are relative to the workspace root, hence the same crates in different checkouts of a
+/// workspace have the same identifiers.
+///
+/// ```
+/// # use pugio_lib::graph::{Graph, NodeId, Source};
+/// fn find_anyhow(graph: &Graph) -> Option<usize> {
+///     graph.find(&NodeId::new("anyhow", "1.0.100", Some(Source::Registry(None))))
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct NodeId {
+    name: String,
+    version: String,
+    source: Option<Source>,
+}
+
+impl NodeId {
+    /// Create a new identifier from the short name, version without the leading `v`, and source of
+    /// a crate, which is `None` for nodes not parsed from `cargo-tree` output, e.g. group nodes.
+    pub fn new(
+        name: impl Into<String>,
+        version: impl Into<String>,
+        source: Option<Source>,
+    ) -> Self {
+        let source = match source {
+            Some(Source::Workspace(path)) => Some(Source::Path(path)),
+            source => source,
+        };
+        Self {
+            name: name.into(),
+            version: version.into(),
+            source,
+        }
+    }
+
+    /// Short name of the crate.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Version of the crate without the leading `v`, e.g. `1.0.0`, or empty if unknown.
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    /// Source of the crate, if known.
+    pub fn source(&self) -> Option<&Source> {
+        self.source.as_ref()
+    }
+}
+
+/// The source of a crate, as parsed from its extra information.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Source {
+    /// A registry, with its name if it is not the default `crates.io`.
+    Registry(Option<String>),
+    /// A git repository, with its URL and the revision if specified.
+    Git { url: String, rev: Option<String> },
+    /// A local path that is not a workspace member, relative to the workspace root if known.
+    Path(String),
+    /// A local path that is a workspace member, as listed by `cargo metadata`, relative to the
+    /// workspace root if known.
+    Workspace(String),
+}
+
+impl Source {
+    /// Parse the source from the extra information of a crate.
+    ///
+    /// For example:
+    /// - `v1.0.0`: [`Registry(None)`](Self::Registry)
+    /// - ``v1.0.0 (registry `my-registry`)``: [`Registry(Some("my-registry"))`](Self::Registry)
+    /// - `v1.0.0 (https://github.com/my4ng/pugio#0123abcd)`: [`Git`](Self::Git)
+    /// - `v1.0.0 (/path/to/crate)`: [`Path`](Self::Path)
+    fn parse(extra: &str) -> Self {
+        let Some((_, rest)) = extra.split_once(" (") else {
+            return Source::Registry(None);
+        };
+        let rest = rest.strip_suffix(')').unwrap_or(rest);
+
+        if let Some(registry) = rest.strip_prefix("registry `") {
+            Source::Registry(Some(registry.trim_end_matches('`').to_string()))
+        } else if rest.contains("://") {
+            match rest.split_once('#') {
+                Some((url, rev)) => Source::Git {
+                    url: url.to_string(),
+                    rev: Some(rev.to_string()),
+                },
+                None => Source::Git {
+                    url: rest.to_string(),
+                    rev: None,
+                },
+            }
+        } else {
+            Source::Path(rest.to_string())
+        }
+    }
+
+    /// Get the kind of the source as a kebab-case string, i.e. one of `"registry"`, `"git"`,
+    /// `"path"` or `"workspace"`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Source::Registry(_) => "registry",
+            Source::Git { .. } => "git",
+            Source::Path(_) => "path",
+            Source::Workspace(_) => "workspace",
+        }
+    }
 }
 
 /// The weight of a directed edge in the dependency graph, representing a binary relation of
 /// dependency of the source node on the target node.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EdgeWeight {
+    #[serde(default)]
     pub(crate) features: BTreeMap<String, Vec<String>>,
 }
 
@@ -554,7 +2009,7 @@ impl EdgeWeight {
 }
 
 /// Options for outputting the graph in DOT format.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct DotOptions {
     /// If `Some(true)`, highlight nodes in downward direction (dependencies) from the root.
     ///
@@ -568,4 +2023,560 @@ pub struct DotOptions {
     pub inverse_gradient: bool,
     /// If `true`, use dark mode for coloring.
     pub dark_mode: bool,
+    /// If `true`, draw clusters around crates of the same source kind, i.e. workspace members,
+    /// local paths, git repositories and registries, see [`Source::kind`].
+    pub cluster: bool,
+    /// If `Some(true)`, hide redundant edges of the transitive reduction, i.e. an edge from a node
+    /// to a dependency that is also reachable through another of its dependencies.
+    ///
+    /// If `Some(false)`, draw redundant edges faintly without affecting the layout.
+    ///
+    /// If `None`, draw all edges normally.
+    pub transitive_reduction: Option<bool>,
+    /// Extra DOT attributes of nodes by their indices, e.g. `style = "dashed"`.
+    ///
+    /// These are appended to the generated attributes, hence take precedence over them.
+    pub node_attributes: HashMap<usize, String>,
+    /// Extra DOT attributes of edges by their source and target node indices, e.g.
+    /// `color = "red"`.
+    ///
+    /// These are appended to the generated attributes, hence take precedence over them.
+    pub edge_attributes: HashMap<(usize, usize), String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json(nodes: &[usize], edges: &[(usize, usize)], root: usize) -> String {
+        let nodes = nodes
+            .iter()
+            .map(|i| {
+                format!(
+                    r#"{{ "index": {i}, "short": "n{i}", "extra": "v1.0.0", "features": {{}}, "members": [], "source": {{ "registry": null }} }}"#
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let edges = edges
+            .iter()
+            .map(|(s, t)| format!(r#"{{ "source": {s}, "target": {t}, "features": {{}} }}"#))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            r#"{{ "version": 1, "root": {root}, "std": null, "nodes": [{nodes}], "edges": [{edges}] }}"#
+        )
+    }
+
+    fn deserialize(json: &str) -> Result<Graph, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+
+    /// `a` depends on `b`, `c` and `e`, `b` and `c` both depend on `d`, which depends on `e`, and
+    /// only `b` depends on `f`. Node `i` has index `i` and size `2^i`.
+    fn diamond() -> Graph {
+        let mut builder = GraphBuilder::new();
+        for (i, name) in ["a", "b", "c", "d", "e", "f"].into_iter().enumerate() {
+            let id = NodeId::new(name, "1.0.0", Some(Source::Registry(None)));
+            builder.add_node(id, &[], Some(1 << i));
+        }
+        for (source, target) in [(0, 1), (0, 2), (0, 4), (1, 3), (2, 3), (3, 4), (1, 5)] {
+            builder.add_edge(source, target, &[]);
+        }
+        builder.build().unwrap()
+    }
+
+    fn neighbors(graph: &Graph, index: usize, outgoing: bool) -> Vec<&str> {
+        let mut names = graph
+            .neighbors(index, outgoing)
+            .map(|i| graph.node_weight(i).short())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn deserialize_valid_graph() {
+        let graph = deserialize(&json(&[1, 0], &[(0, 1)], 0)).unwrap();
+        assert_eq!(graph.node_count(), 2);
+        assert_eq!(graph.node_weight(1).short(), "n1");
+    }
+
+    #[test]
+    fn deserialize_rejects_out_of_range_index() {
+        for index in [2, usize::MAX] {
+            let error = deserialize(&json(&[0, index], &[(0, 1)], 0)).unwrap_err();
+            assert!(
+                error.contains(&format!("invalid node index {index}")),
+                "{error}"
+            );
+        }
+    }
+
+    #[test]
+    fn deserialize_rejects_duplicate_index() {
+        let error = deserialize(&json(&[0, 0], &[], 0)).unwrap_err();
+        assert!(error.contains("duplicate node index 0"), "{error}");
+    }
+
+    #[test]
+    fn deserialize_rejects_dangling_edge() {
+        let error = deserialize(&json(&[0, 1], &[(0, 1), (1, 7)], 0)).unwrap_err();
+        assert!(error.contains("missing node index 7"), "{error}");
+
+        let error = deserialize(&json(&[0], &[], usize::MAX)).unwrap_err();
+        assert!(
+            error.contains(&format!("missing node index {}", usize::MAX)),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn exclusive_sizes_follow_dominators() {
+        let graph = diamond();
+        let sizes = graph
+            .node_indices()
+            .map(|i| graph.exclusive_size(i))
+            .collect::<Vec<_>>();
+
+        // `d` and `e` are reachable through both `b` and `c`, and `e` also directly from `a`
+        assert_eq!(sizes, [63, 2 + 32, 4, 8, 16, 32]);
+    }
+
+    #[test]
+    fn exclusive_sizes_are_recomputed_after_mutation() {
+        let mut graph = diamond();
+        assert_eq!(graph.exclusive_size(1), 34);
+
+        graph.remove_indices(std::iter::once(2));
+        assert_eq!(graph.exclusive_size(1), 2 + 8 + 32);
+        assert_eq!(graph.exclusive_size(0), 63 - 4);
+    }
+
+    #[test]
+    fn paths_to_limits_paths_but_counts_all() {
+        let graph = diamond();
+
+        let (paths, count) = graph.paths_to(4, 2);
+        assert_eq!(count, 3);
+        assert_eq!(paths.len(), 2);
+        for path in paths {
+            assert_eq!(path.first(), Some(&0));
+            assert_eq!(path.last(), Some(&4));
+        }
+
+        let (paths, count) = graph.paths_to(4, usize::MAX);
+        assert_eq!(count, 3);
+        assert!(paths.contains(&vec![0, 4]));
+    }
+
+    #[test]
+    fn path_counts_sum_over_dependents() {
+        let graph = diamond();
+        assert_eq!(graph.path_counts(), [1, 1, 1, 2, 3, 1]);
+    }
+
+    #[test]
+    fn redundant_edges_of_transitive_reduction() {
+        let graph = diamond();
+        let edges = graph
+            .redundant_edges()
+            .into_iter()
+            .map(|(s, t)| (s.index(), t.index()))
+            .collect::<Vec<_>>();
+
+        // `e` is also reachable from `a` through `d`
+        assert_eq!(edges, [(0, 4)]);
+    }
+
+    #[test]
+    fn focus_upwards_replaces_removed_root_with_ellipsis() {
+        let mut graph = diamond();
+        let ellipsis = graph.focus(std::iter::once(3), 1, 0).unwrap();
+
+        assert_eq!(graph.root(), ellipsis);
+        assert!(graph.node_weight(ellipsis).is_ellipsis());
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(neighbors(&graph, ellipsis, true), ["b", "c"]);
+        assert_eq!(neighbors(&graph, 3, false), ["b", "c"]);
+        assert_eq!(neighbors(&graph, 3, true), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn focus_downwards_keeps_dependencies_within_reach() {
+        let mut graph = diamond();
+        let ellipsis = graph.focus(std::iter::once(1), 0, 1).unwrap();
+
+        assert_eq!(graph.root(), ellipsis);
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(neighbors(&graph, ellipsis, true), ["b"]);
+        assert_eq!(neighbors(&graph, 1, true), ["d", "f"]);
+        assert_eq!(neighbors(&graph, 3, true), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn focus_keeps_root_and_adds_ellipsis_for_orphans() {
+        let mut graph = diamond();
+        let ellipsis = graph.focus([0, 5].into_iter(), 0, 0).unwrap();
+
+        // `f` lost its only dependent `b`, so it hangs off the ellipsis under the root
+        assert_eq!(graph.root(), 0);
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(neighbors(&graph, 0, true), ["…"]);
+        assert_eq!(neighbors(&graph, ellipsis, true), ["f"]);
+    }
+
+    #[test]
+    fn focus_within_reach_adds_no_ellipsis() {
+        let mut graph = diamond();
+        assert_eq!(graph.focus(std::iter::once(0), 0, 2), None);
+        assert_eq!(graph.node_count(), 6);
+    }
+
+    #[test]
+    fn builder_merges_features_of_repeated_edges() {
+        let mut builder = GraphBuilder::new();
+        let registry = || Some(Source::Registry(None));
+        let app = builder.add_node(NodeId::new("app", "0.1.0", registry()), &[], None);
+        let serde = builder.add_node(NodeId::new("serde", "1.0.0", registry()), &[], None);
+        builder.add_edge(app, serde, &[("default", "a")]);
+        builder.add_edge(app, serde, &[("x", "b"), ("default", "c")]);
+        let graph = builder.build().unwrap();
+
+        assert_eq!(graph.edges().count(), 1);
+        let features = &graph.edge_weight(app, serde).features;
+        assert_eq!(features["default"], ["a", "c"]);
+        assert_eq!(features["x"], ["b"]);
+    }
+
+    #[test]
+    fn view_and_subgraph_leave_graph_untouched() {
+        let graph = diamond();
+
+        let view = graph.view(|g| g.remove_deep_deps(1));
+        assert_eq!(neighbors(&view, 0, true), ["b", "c", "e"]);
+        assert_eq!(view.node_count(), 4);
+
+        let subgraph = graph.subgraph([1, 3, 99].into_iter());
+        assert_eq!(subgraph.node_count(), 3);
+        assert_eq!(neighbors(&subgraph, 1, true), ["d"]);
+        assert_eq!(subgraph.node_weight(3).short(), "d");
+
+        assert_eq!(graph.node_count(), 6);
+    }
+
+    #[test]
+    fn source_parse() {
+        let git = |url: &str, rev: Option<&str>| Source::Git {
+            url: url.to_string(),
+            rev: rev.map(str::to_string),
+        };
+        let cases = [
+            ("v1.0.0", Source::Registry(None)),
+            (
+                "v1.0.0 (registry `my-registry`)",
+                Source::Registry(Some("my-registry".to_string())),
+            ),
+            (
+                "v1.0.0 (https://github.com/my4ng/pugio#0123abcd)",
+                git("https://github.com/my4ng/pugio", Some("0123abcd")),
+            ),
+            (
+                "v1.0.0 (https://github.com/my4ng/pugio?branch=dev#0123abcd)",
+                git(
+                    "https://github.com/my4ng/pugio?branch=dev",
+                    Some("0123abcd"),
+                ),
+            ),
+            (
+                "v1.0.0 (https://github.com/my4ng/pugio)",
+                git("https://github.com/my4ng/pugio", None),
+            ),
+            (
+                "v1.0.0 (/path/to/crate)",
+                Source::Path("/path/to/crate".to_string()),
+            ),
+        ];
+
+        for (extra, source) in cases {
+            assert_eq!(Source::parse(extra), source, "{extra}");
+        }
+    }
+
+    #[test]
+    fn workspace_members_from_cargo_metadata() {
+        let metadata = r#"{
+            "workspace_root": "/ws",
+            "workspace_members": ["path+file:///ws/app#0.1.0"],
+            "packages": [
+                { "id": "path+file:///ws/app#0.1.0", "manifest_path": "/ws/app/Cargo.toml" },
+                { "id": "path+file:///ws/util#0.1.0", "manifest_path": "/ws/util/Cargo.toml" }
+            ]
+        }"#;
+
+        let mut builder = GraphBuilder::new();
+        let mut add = |name, path: &str| {
+            let id = NodeId::new(name, "0.1.0", Some(Source::Path(path.to_string())));
+            builder.add_node(id, &[], None)
+        };
+        let (app, util, vendored) = (
+            add("app", "/ws/app"),
+            add("util", "/ws/util"),
+            add("x", "/x"),
+        );
+        builder.add_edge(app, util, &[]);
+        builder.add_edge(app, vendored, &[]);
+        let mut graph = builder.build().unwrap();
+        apply_workspace(&mut graph.inner, &get_workspace(metadata));
+
+        let sources = [app, util, vendored].map(|i| graph.node_weight(i).source().cloned());
+        assert_eq!(
+            sources,
+            [
+                Some(Source::Workspace("app".to_string())),
+                Some(Source::Path("util".to_string())),
+                Some(Source::Path("../x".to_string())),
+            ]
+        );
+        assert_eq!(
+            graph.node_id(app),
+            NodeId::new("app", "0.1.0", Some(Source::Workspace("app".to_string())))
+        );
+    }
+
+    #[test]
+    fn cluster_output_groups_workspace_members() {
+        use crate::{
+            coloring::NodeColoringGradient,
+            template::{Template, TemplateOptions},
+        };
+
+        let mut builder = GraphBuilder::new();
+        let app_id = NodeId::new("app", "0.1.0", Some(Source::Path("/ws/app".to_string())));
+        let app = builder.add_node(app_id, &[], Some(1024));
+        let util_id = NodeId::new("util", "0.1.0", Some(Source::Path("/ws/util".to_string())));
+        let util = builder.add_node(util_id, &[], Some(1024));
+        let serde_id = NodeId::new("serde", "1.0.0", Some(Source::Registry(None)));
+        let serde = builder.add_node(serde_id, &[], Some(1024));
+        builder.add_edge(app, util, &[]);
+        builder.add_edge(util, serde, &[]);
+        builder.set_workspace_members(&["/ws/app", "/ws/util"]);
+        let graph = builder.build().unwrap();
+
+        let template_options = TemplateOptions::default();
+        let template = Template::new(&template_options).unwrap();
+        let dot_options = DotOptions {
+            cluster: true,
+            ..Default::default()
+        };
+        let dot = graph.output_dot(
+            &dot_options,
+            &template,
+            &None,
+            &NodeColoringGradient::default(),
+        );
+
+        // Node indices are listed on the last line of each cluster
+        let cluster = |kind| {
+            let (_, rest) = dot
+                .split_once(&format!("subgraph cluster_{kind} {{"))
+                .unwrap();
+            let (body, _) = rest.split_once('}').unwrap();
+            body.lines().rfind(|l| !l.trim().is_empty()).unwrap().trim()
+        };
+        assert_eq!(cluster("workspace"), format!("{app}; {util}"));
+        assert_eq!(cluster("registry"), serde.to_string());
+    }
+
+    #[test]
+    fn retain_paths_to_keeps_all_paths() {
+        let mut graph = diamond();
+        graph.retain_paths_to([5, 99].into_iter());
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(neighbors(&graph, 0, true), ["b"]);
+        assert_eq!(neighbors(&graph, 1, true), ["f"]);
+
+        let mut graph = diamond();
+        graph.retain_paths_to(std::iter::once(4));
+        assert_eq!(graph.node_count(), 5);
+        assert_eq!(neighbors(&graph, 4, false), ["a", "d"]);
+    }
+
+    #[test]
+    fn contract_indices_connects_dependents_to_dependencies() {
+        let mut graph = diamond();
+        graph.contract_indices([0, 3, 99].into_iter());
+
+        // The root is skipped, and `d` is replaced by edges from `b` and `c` to `e`
+        assert_eq!(graph.node_count(), 5);
+        assert_eq!(neighbors(&graph, 1, true), ["e", "f"]);
+        assert_eq!(neighbors(&graph, 2, true), ["e"]);
+        assert!(graph.edge_weight(1, 4).features().is_empty());
+        assert_eq!(graph.exclusive_size(0), 63 - 8);
+    }
+
+    #[test]
+    fn retain_top_keeps_heaviest_and_paths_to_them() {
+        use crate::coloring::NodeColoringScheme;
+
+        let exclusive = |graph: &Graph| {
+            NodeColoringValues::new(graph, NodeColoringScheme::Exclusive, Default::default())
+        };
+
+        let mut graph = diamond();
+        let values = exclusive(&graph);
+        graph.retain_top(2, &values);
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(neighbors(&graph, 1, true), ["f"]);
+
+        // Values of removed nodes are skipped
+        let mut graph = diamond();
+        let values = exclusive(&graph);
+        graph.remove_indices(std::iter::once(1));
+        graph.retain_top(1, &values);
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(neighbors(&graph, 4, false), ["a", "d"]);
+    }
+
+    #[test]
+    fn duplicates_by_short_name() {
+        assert!(diamond().duplicates().is_empty());
+
+        let mut graph = GraphBuilder::star(&[
+            ("app", "0.1.0", 1000),
+            ("syn", "1.0.0", 1000),
+            ("syn", "2.0.0", 3000),
+            ("serde", "1.0.0", 500),
+        ]);
+        let duplicates = graph.duplicates();
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates["syn"], [1, 2]);
+
+        // Sizes reported by short name are divided equally among the nodes
+        assert_eq!(graph.size(1), Some(2000));
+        assert_eq!(graph.size(2), Some(2000));
+
+        graph.remove_indices(std::iter::once(2));
+        assert!(graph.duplicates().is_empty());
+    }
+
+    #[test]
+    fn heaviest_and_longest_paths() {
+        let mut graph = diamond();
+        assert_eq!(graph.heaviest_path(), [0, 1, 5]);
+        // Ties are broken by the lower node index
+        assert_eq!(graph.longest_path(), [0, 1, 3, 4]);
+
+        graph.remove_indices(std::iter::once(1));
+        assert_eq!(graph.heaviest_path(), [0, 2, 3, 4]);
+        assert_eq!(graph.longest_path(), [0, 2, 3, 4]);
+    }
+
+    #[test]
+    fn node_id_and_find() {
+        let mut builder = GraphBuilder::new();
+        let app_id = NodeId::new("app", "0.1.0", Some(Source::Path("/ws/app".to_string())));
+        let app = builder.add_node(app_id, &[], None);
+        let serde_id = NodeId::new("serde", "1.0.0", Some(Source::Registry(None)));
+        let serde = builder.add_node(serde_id.clone(), &[], None);
+        builder.add_edge(app, serde, &[]);
+        builder.set_workspace_members(&["/ws/app"]);
+        let mut graph = builder.build().unwrap();
+
+        // Workspace members are identified by their paths
+        let app_id = NodeId::new("app", "0.1.0", Some(Source::Path("/ws/app".to_string())));
+        assert_eq!(graph.node_id(app), app_id);
+        assert_eq!(
+            NodeId::new(
+                "app",
+                "0.1.0",
+                Some(Source::Workspace("/ws/app".to_string()))
+            ),
+            app_id
+        );
+        assert_eq!(graph.node_id(serde), serde_id);
+
+        assert_eq!(graph.find(&app_id), Some(app));
+        assert_eq!(graph.find(&serde_id), Some(serde));
+        assert_eq!(
+            graph.find(&NodeId::new("serde", "2.0.0", serde_id.source().cloned())),
+            None
+        );
+        assert_eq!(graph.find(&NodeId::new("serde", "1.0.0", None)), None);
+
+        graph.remove_indices(std::iter::once(serde));
+        assert_eq!(graph.find(&serde_id), None);
+    }
+
+    #[test]
+    fn edges_and_edges_directed() {
+        let mut builder = GraphBuilder::new();
+        let registry = || Some(Source::Registry(None));
+        let app = builder.add_node(NodeId::new("app", "0.1.0", registry()), &[], None);
+        let serde = builder.add_node(NodeId::new("serde", "1.0.0", registry()), &[], None);
+        let json = builder.add_node(NodeId::new("serde_json", "1.0.0", registry()), &[], None);
+        builder.add_edge(app, serde, &[("default", "derive")]);
+        builder.add_edge(app, json, &[]);
+        builder.add_edge(json, serde, &[("default", "std")]);
+        let graph = builder.build().unwrap();
+
+        let mut edges = graph
+            .edges()
+            .map(|(s, t, w)| (s, t, w.features().keys().cloned().collect::<Vec<_>>()))
+            .collect::<Vec<_>>();
+        edges.sort();
+        assert_eq!(
+            edges,
+            [
+                (app, serde, vec!["default".to_string()]),
+                (app, json, vec![]),
+                (json, serde, vec!["default".to_string()]),
+            ]
+        );
+
+        let mut incoming = graph
+            .edges_directed(serde, false)
+            .map(|(s, t, w)| (s, t, w.features()["default"].clone()))
+            .collect::<Vec<_>>();
+        incoming.sort();
+        assert_eq!(
+            incoming,
+            [
+                (app, serde, vec!["derive".to_string()]),
+                (json, serde, vec!["std".to_string()]),
+            ]
+        );
+
+        let outgoing = graph.edges_directed(app, true).map(|(_, t, _)| t);
+        assert_eq!(
+            outgoing.collect::<HashSet<_>>(),
+            HashSet::from([serde, json])
+        );
+        assert_eq!(graph.edges_directed(serde, true).count(), 0);
+        assert_eq!(graph.edges_directed(99, true).count(), 0);
+    }
+
+    #[test]
+    fn group_name_conflicts_with_non_member() {
+        let mut graph = deserialize(&json(&[0, 1, 2], &[(0, 1), (0, 2)], 0)).unwrap();
+
+        let error = graph.group("n2", std::iter::once(1)).unwrap_err();
+        assert!(matches!(error, GroupError::Conflict(name) if name == "n2"));
+
+        let group = graph.group("n1", [1, 2].into_iter()).unwrap();
+        assert_eq!(
+            graph.node_weight(group).members(),
+            ["n1 v1.0.0", "n2 v1.0.0"]
+        );
+    }
+
+    #[test]
+    fn serialize_round_trip_after_removal() {
+        let mut graph = deserialize(&json(&[0, 1, 2], &[(0, 1), (0, 2)], 0)).unwrap();
+        graph.remove_indices(std::iter::once(1));
+
+        let graph = deserialize(&serde_json::to_string(&graph).unwrap()).unwrap();
+        assert_eq!(graph.node_count(), 2);
+        assert_eq!(graph.node_weight(1).short(), "n2");
+    }
 }
@@ -20,8 +20,16 @@ use pugio_lib::coloring::{Color, Gradient, Values};
 let cargo_tree_output = "...";
 // Output of `cargo bloat -n0 --message-format=json --crates ...`
 let cargo_bloat_output = "...";
+// Output of `cargo metadata --format-version=1 --no-deps ...`
+let cargo_metadata_output = "...";
 
-let mut graph = Graph::new(cargo_tree_output, cargo_bloat_output, false, None);
+let mut graph = Graph::new(
+    cargo_tree_output,
+    cargo_bloat_output,
+    cargo_metadata_output,
+    false,
+    None,
+);
 
 // Remove dependencies more than 3 levels deep.
 graph.remove_deep_deps(3);
@@ -134,6 +142,9 @@ let dot_output = graph.output_dot(&Default::default(), &CustomTemplate, &custom_
 
 mod cargo;
 pub mod coloring;
+pub mod diff;
 pub mod error;
+pub mod filter;
 pub mod graph;
 pub mod template;
+pub mod union;
@@ -38,11 +38,15 @@ pub trait Templating {
 /// - `size`: Size of the node in bytes.
 /// - `size_binary`: Size of the node in binary format (e.g., "1.0 KiB").
 /// - `size_decimal`: Size of the node in decimal format (e.g., "1.0 kB").
+/// - `exclusive`: Exclusive size of the node in bytes, see [`Graph::exclusive_size`].
+/// - `exclusive_binary`: Exclusive size of the node in binary format.
+/// - `exclusive_decimal`: Exclusive size of the node in decimal format.
 /// - `scheme`: Coloring scheme used (if any).
 /// - `value`: Value used for coloring (if any).
 /// - `value_binary`: Value used for coloring in binary format (if any).
 /// - `value_decimal`: Value used for coloring in decimal format (if any).
 /// - `features`: Features of the node.
+/// - `members`: Members of the node if it is a group, see [`Graph::group`].
 ///
 /// # Edge template values
 /// - `source`: Short name of the source node.
@@ -68,7 +72,7 @@ impl<'a> Template<'a> {
     ///
     /// If a template option field is `None`, its corresponding default is used:
     /// - Node label: `"{short}"`
-    /// - Node tooltip: `"{full}\n{size_binary}\n{features}"`
+    /// - Node tooltip: `"{full}\n{size_binary}\n{features}{{ if members }}\n{members}{{ endif }}"`
     /// - Edge label: `"{features}"`
     /// - Edge tooltip: `"{source} -> {target}"`
     pub fn new(template_options: &'a TemplateOptions) -> Result<Self, TemplateError> {
@@ -82,10 +86,9 @@ impl<'a> Template<'a> {
         )?;
         template.add_template(
             "node_tooltip",
-            template_options
-                .node_tooltip_template
-                .as_deref()
-                .unwrap_or("{full}\n{size_binary}\n{features}"),
+            template_options.node_tooltip_template.as_deref().unwrap_or(
+                "{full}\n{size_binary}\n{features}{{ if members }}\n{members}{{ endif }}",
+            ),
         )?;
         template.add_template(
             "edge_label",
@@ -124,15 +127,20 @@ impl<'a> Templating for Template<'a> {
             size: usize,
             size_binary: String,
             size_decimal: String,
+            exclusive: usize,
+            exclusive_binary: String,
+            exclusive_decimal: String,
             scheme: Option<&'static str>,
             value: Option<usize>,
             value_binary: Option<String>,
             value_decimal: Option<String>,
             features: String,
+            members: String,
         }
 
         let node = graph.node_weight(index);
         let size = graph.size(index).unwrap_or_default();
+        let exclusive = graph.exclusive_size(index);
 
         let context = NodeContext {
             short: node.short(),
@@ -141,11 +149,15 @@ impl<'a> Templating for Template<'a> {
             size,
             size_binary: humansize::format_size(size, humansize::BINARY),
             size_decimal: humansize::format_size(size, humansize::DECIMAL),
+            exclusive,
+            exclusive_binary: humansize::format_size(exclusive, humansize::BINARY),
+            exclusive_decimal: humansize::format_size(exclusive, humansize::DECIMAL),
             scheme: context.map(Into::into),
             value,
             value_binary: value.map(|v| humansize::format_size(v, humansize::BINARY)),
             value_decimal: value.map(|v| humansize::format_size(v, humansize::DECIMAL)),
             features: features(&node.features),
+            members: node.members.join(",\n"),
         };
 
         let label = self
@@ -0,0 +1,369 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::{
+    coloring::Values,
+    graph::{EdgeWeight, Graph, NodeId, NodeWeight, node_width},
+    template::Templating,
+};
+
+/// Union of dependency graphs, e.g. of multiple binaries from the same workspace.
+///
+/// Nodes are merged by their [`NodeId`]s, and the size of each node in each source graph is kept.
+/// If the roots of the source graphs differ, a synthetic `union` root node depending on all of
+/// them is added. Edges that would create a cycle are skipped, and the `std` standalone node is
+/// only kept from the first graph.
+///
+/// It implements both [`Values`] and [`Templating`] to color nodes by membership, i.e. with the
+/// source graph index if the node is only in that graph, or `None` if shared, to be used with
+/// [`NodeColoringCategoricalGradient`](crate::coloring::NodeColoringCategoricalGradient).
+///
+/// ```
+/// # use pugio_lib::graph::Graph;
+/// use pugio_lib::coloring::NodeColoringCategoricalGradient;
+///
+/// fn output(cli: Graph, daemon: Graph) -> String {
+///     let union = Graph::union(&[cli, daemon]);
+///     let gradient = NodeColoringCategoricalGradient::default();
+///
+///     union
+///         .graph()
+///         .output_dot(&Default::default(), &union, &union, &gradient)
+/// }
+/// ```
+///
+/// Alternatively, each source graph can be output as a panel of the merged graph with
+/// [`panel`](GraphUnion::panel), with [`panel_node_attributes`](GraphUnion::panel_node_attributes)
+/// and [`panel_edge_attributes`](GraphUnion::panel_edge_attributes) hiding what is not in that
+/// source graph, so that all panels share the same layout.
+#[derive(Debug)]
+pub struct GraphUnion {
+    graph: Graph,
+    sizes: Vec<Vec<Option<usize>>>,
+    edges: Vec<HashSet<(usize, usize)>>,
+}
+
+impl Graph {
+    /// Create the union of the given graphs.
+    ///
+    /// # Panics
+    /// Panics if no graphs are given.
+    pub fn union(graphs: &[Graph]) -> GraphUnion {
+        let mut graph = graphs[0].clone();
+        let mut sizes: Vec<Vec<Option<usize>>> = Vec::new();
+        let mut edges = vec![HashSet::new(); graphs.len()];
+
+        let mut id_map: HashMap<NodeId, usize> = graph
+            .node_indices()
+            .map(|i| (graph.node_id(i), i))
+            .collect();
+
+        let mut roots = Vec::new();
+        for (source, other) in graphs.iter().enumerate() {
+            // Index of the source graph to index of the merged graph
+            let mut index_map = HashMap::with_capacity(other.node_count());
+            for index in other.node_indices() {
+                let id = other.node_id(index);
+                let merged_index = match id_map.get(&id) {
+                    Some(merged_index) => *merged_index,
+                    None if Some(index) == other.std() => continue,
+                    None => {
+                        let weight = other.node_weight(index).clone();
+                        let merged_index = graph.add_node(weight, other.size(index));
+                        id_map.insert(id, merged_index);
+                        merged_index
+                    }
+                };
+                index_map.insert(index, merged_index);
+
+                if sizes.len() <= merged_index {
+                    sizes.resize(merged_index + 1, vec![None; graphs.len()]);
+                }
+                sizes[merged_index][source] = Some(other.size(index).unwrap_or_default());
+            }
+
+            for (source_index, target_index, weight) in other.edges() {
+                let (source_index, target_index) =
+                    (index_map[&source_index], index_map[&target_index]);
+                graph.add_edge(source_index, target_index, weight.clone());
+                edges[source].insert((source_index, target_index));
+            }
+
+            roots.push(index_map[&other.root()]);
+        }
+
+        if roots.iter().any(|r| *r != roots[0]) {
+            let root = graph.add_node(NodeWeight::synthetic("union"), None);
+            for (source, index) in roots.into_iter().enumerate() {
+                graph.add_edge(
+                    root,
+                    index,
+                    EdgeWeight {
+                        features: BTreeMap::new(),
+                    },
+                );
+                edges[source].insert((root, index));
+            }
+            graph.change_root(root);
+        }
+
+        sizes.resize(graph.node_capacity(), vec![None; graphs.len()]);
+
+        GraphUnion {
+            graph,
+            sizes,
+            edges,
+        }
+    }
+}
+
+impl GraphUnion {
+    /// Get the merged graph.
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    /// Get the number of source graphs.
+    pub fn source_count(&self) -> usize {
+        self.sizes.first().map(Vec::len).unwrap_or_default()
+    }
+
+    /// Get the sizes of the node at the given index of the merged graph in each source graph, or
+    /// `None` if it is not in that graph.
+    pub fn sizes(&self, index: usize) -> &[Option<usize>] {
+        &self.sizes[index]
+    }
+
+    /// Get the index of the only source graph containing the node at the given index of the merged
+    /// graph, or `None` if it is shared by multiple source graphs, or is the synthetic root.
+    pub fn only_in(&self, index: usize) -> Option<usize> {
+        let mut sources = self.sizes[index]
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.is_some())
+            .map(|(i, _)| i);
+        match (sources.next(), sources.next()) {
+            (Some(source), None) => Some(source),
+            _ => None,
+        }
+    }
+
+    /// Get the merged graph with the sizes of the given source graph, where nodes not in that
+    /// graph have no size. The node indices are the same as in the merged graph.
+    ///
+    /// Values computed from it, e.g. [`NodeColoringValues`](crate::coloring::NodeColoringValues),
+    /// can be made comparable across panels with
+    /// [`set_max`](crate::coloring::NodeColoringValues::set_max). To compute values of the source
+    /// graph alone, remove the nodes not in it first, e.g. with [`Graph::view`].
+    ///
+    /// # Panics
+    /// Panics if the source index is out of bounds.
+    pub fn panel(&self, source: usize) -> Graph {
+        assert!(source < self.source_count());
+
+        let mut graph = self.graph.clone();
+        for index in self.graph.node_indices() {
+            graph.set_size(index, self.sizes[index][source]);
+        }
+        graph
+    }
+
+    /// Get the DOT node attributes of the panel of the given source graph, to be used in
+    /// [`DotOptions::node_attributes`](crate::graph::DotOptions::node_attributes).
+    ///
+    /// Each node has the width of its largest size across all source graphs, so that the layouts
+    /// of all panels are the same, and nodes not in the source graph are invisible.
+    pub fn panel_node_attributes(&self, source: usize) -> HashMap<usize, String> {
+        self.graph
+            .node_indices()
+            .map(|index| {
+                let sizes = &self.sizes[index];
+                let size = sizes.iter().flatten().max().copied().unwrap_or_default();
+                let mut attributes = format!("width = {}", node_width(size));
+                if sizes.get(source).copied().flatten().is_none() && index != self.graph.root() {
+                    attributes.push_str(r#" style = "invis""#);
+                }
+                (index, attributes)
+            })
+            .collect()
+    }
+
+    /// Get the DOT edge attributes of the panel of the given source graph, to be used in
+    /// [`DotOptions::edge_attributes`](crate::graph::DotOptions::edge_attributes).
+    ///
+    /// Edges not in the source graph are invisible.
+    pub fn panel_edge_attributes(&self, source: usize) -> HashMap<(usize, usize), String> {
+        self.graph
+            .edges()
+            .map(|(s, t, _)| (s, t))
+            .filter(|edge| !self.edges[source].contains(edge))
+            .map(|edge| (edge, r#"style = "invis""#.to_string()))
+            .collect()
+    }
+}
+
+impl Values for GraphUnion {
+    type Context = ();
+    type Value = Vec<Option<usize>>;
+    type Output = Option<usize>;
+
+    fn context(&self) -> Self::Context {}
+
+    fn value(&self, index: usize) -> Self::Value {
+        self.sizes[index].clone()
+    }
+
+    /// The index of the only source graph containing the node, or `None` if shared.
+    fn output(&self, index: usize) -> Self::Output {
+        self.only_in(index)
+    }
+}
+
+impl Templating for GraphUnion {
+    type Context = ();
+    type Value = Vec<Option<usize>>;
+
+    fn node(
+        &self,
+        graph: &Graph,
+        index: usize,
+        value: Self::Value,
+        _context: Self::Context,
+    ) -> (String, String) {
+        let node = graph.node_weight(index);
+        let sizes = value
+            .iter()
+            .enumerate()
+            .map(|(i, size)| {
+                let size = size
+                    .map(|s| humansize::format_size(s, humansize::BINARY))
+                    .unwrap_or_else(|| "-".to_string());
+                format!("#{i}: {size}")
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        (
+            node.short().to_string(),
+            format!("{}\n{sizes}", node.full()),
+        )
+    }
+
+    fn edge(&self, graph: &Graph, source: usize, target: usize) -> (String, String) {
+        let source = graph.node_weight(source).short();
+        let target = graph.node_weight(target).short();
+        (String::new(), format!("{source} -> {target}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{GraphBuilder, Source};
+
+    fn cli_and_daemon() -> GraphUnion {
+        let cli = GraphBuilder::star(&[
+            ("cli", "0.1.0", 1000),
+            ("clap", "4.0.0", 3000),
+            ("serde", "1.0.0", 1000),
+        ]);
+        let daemon = GraphBuilder::star(&[
+            ("daemon", "0.1.0", 2000),
+            ("tokio", "1.0.0", 4000),
+            ("serde", "1.0.0", 1200),
+        ]);
+        Graph::union(&[cli, daemon])
+    }
+
+    #[test]
+    fn membership_and_sizes() {
+        let union = cli_and_daemon();
+        let graph = union.graph();
+        let root = graph.root();
+
+        assert_eq!(union.source_count(), 2);
+        assert_eq!(graph.node_weight(root).short(), "union");
+        assert_eq!(graph.node_count(), 6);
+
+        assert_eq!(union.only_in(graph.index_of("clap")), Some(0));
+        assert_eq!(union.only_in(graph.index_of("tokio")), Some(1));
+        assert_eq!(union.only_in(graph.index_of("serde")), None);
+        assert_eq!(union.only_in(root), None);
+        assert_eq!(
+            union.sizes(graph.index_of("serde")),
+            [Some(1000), Some(1200)]
+        );
+    }
+
+    #[test]
+    fn versions_only_in_one_source_keep_their_sizes() {
+        let union = Graph::union(&[
+            GraphBuilder::star(&[("app", "0.1.0", 1000), ("syn", "1.0.0", 1000)]),
+            GraphBuilder::star(&[("app", "0.1.0", 1000), ("syn", "2.0.0", 5000)]),
+        ]);
+        let graph = union.graph();
+        let find = |version| {
+            let id = NodeId::new("syn", version, Some(Source::Registry(None)));
+            graph.find(&id).unwrap()
+        };
+        let (syn1, syn2) = (find("1.0.0"), find("2.0.0"));
+
+        assert_eq!(graph.size(syn1), Some(1000));
+        assert_eq!(graph.size(syn2), Some(5000));
+        assert_eq!(union.panel(0).size(syn2), None);
+        assert_eq!(union.panel(1).size(syn1), None);
+        assert_eq!(union.panel(1).size(syn2), Some(5000));
+    }
+
+    #[test]
+    fn shared_root_is_kept() {
+        let union = Graph::union(&[
+            GraphBuilder::star(&[("app", "0.1.0", 1000), ("clap", "4.0.0", 3000)]),
+            GraphBuilder::star(&[("app", "0.1.0", 1000), ("tokio", "1.0.0", 4000)]),
+        ]);
+        let graph = union.graph();
+
+        assert_eq!(graph.node_weight(graph.root()).short(), "app");
+        assert_eq!(graph.node_count(), 3);
+    }
+
+    #[test]
+    fn panels_have_source_sizes() {
+        let union = cli_and_daemon();
+        let graph = union.graph();
+        let (serde, tokio) = (graph.index_of("serde"), graph.index_of("tokio"));
+
+        let cli = union.panel(0);
+        assert_eq!(cli.size(serde), Some(1000));
+        assert_eq!(cli.size(tokio), None);
+
+        let daemon = union.panel(1);
+        assert_eq!(daemon.size(serde), Some(1200));
+        assert_eq!(daemon.size(tokio), Some(4000));
+    }
+
+    #[test]
+    fn panel_attributes_hide_other_sources() {
+        let union = cli_and_daemon();
+        let graph = union.graph();
+        let root = graph.root();
+        let (daemon, serde, tokio) = (
+            graph.index_of("daemon"),
+            graph.index_of("serde"),
+            graph.index_of("tokio"),
+        );
+
+        let nodes = union.panel_node_attributes(0);
+        assert!(nodes[&tokio].contains("invis"));
+        assert!(!nodes[&serde].contains("invis"));
+        assert!(!nodes[&root].contains("invis"));
+        assert_eq!(nodes[&serde], union.panel_node_attributes(1)[&serde]);
+        assert_eq!(nodes[&serde], format!("width = {}", node_width(1200)));
+
+        let edges = union.panel_edge_attributes(0);
+        let mut hidden = edges.keys().copied().collect::<Vec<_>>();
+        hidden.sort();
+        let mut expected = vec![(root, daemon), (daemon, tokio), (daemon, serde)];
+        expected.sort();
+        assert_eq!(hidden, expected);
+    }
+}
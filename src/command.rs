@@ -8,6 +8,7 @@ use pugio_lib::graph::Graph;
 
 #[derive(Debug, Default)]
 pub struct CargoOptions {
+    pub manifest_path: Option<String>,
     pub package: Option<String>,
     pub bin: Option<String>,
     pub features: Option<String>,
@@ -25,6 +26,10 @@ pub fn cargo_tree_output(options: &CargoOptions) -> anyhow::Result<String> {
         .arg("--prefix=depth")
         .arg("--color=never");
 
+    if let Some(manifest_path) = &options.manifest_path {
+        command.arg(format!("--manifest-path={manifest_path}"));
+    }
+
     if let Some(package) = &options.package {
         command.arg(format!("--package={package}"));
     }
@@ -49,6 +54,27 @@ pub fn cargo_tree_output(options: &CargoOptions) -> anyhow::Result<String> {
         .context("failed to wait on cargo-tree")
 }
 
+pub fn cargo_metadata_output(options: &CargoOptions) -> anyhow::Result<String> {
+    let mut command = Command::new("cargo");
+    command
+        .stdout(Stdio::piped())
+        .arg("metadata")
+        .arg("--format-version=1")
+        .arg("--no-deps")
+        .arg("--color=never");
+
+    if let Some(manifest_path) = &options.manifest_path {
+        command.arg(format!("--manifest-path={manifest_path}"));
+    }
+
+    command
+        .spawn()
+        .context("failed to execute cargo-metadata")?
+        .wait_with_output()
+        .map(|o| String::from_utf8(o.stdout).unwrap())
+        .context("failed to wait on cargo-metadata")
+}
+
 pub fn cargo_bloat_output(options: &CargoOptions) -> anyhow::Result<String> {
     let mut command = Command::new("cargo");
     command
@@ -58,6 +84,10 @@ pub fn cargo_bloat_output(options: &CargoOptions) -> anyhow::Result<String> {
         .arg("--message-format=json")
         .arg("--crates");
 
+    if let Some(manifest_path) = &options.manifest_path {
+        command.arg(format!("--manifest-path={manifest_path}"));
+    }
+
     if let Some(package) = &options.package {
         command.arg(format!("--package={package}"));
     }
@@ -107,12 +137,105 @@ pub fn output_svg(
     output_filename: &str,
     svg_options: &SvgOptions,
 ) -> anyhow::Result<()> {
+    let svg = render_svg(dot_output, graph, svg_options)?;
+    write_svg(&svg, output_filename, svg_options)
+}
+
+/// Output panels of the same graph side by side in a single SVG, each with its title above it.
+pub fn output_panels_svg(
+    panels: &[(String, String)],
+    graph: &Graph,
+    output_filename: &str,
+    svg_options: &SvgOptions,
+) -> anyhow::Result<()> {
+    let title_font_size = 24.0 * svg_options.scale_factor.unwrap_or(1.0);
+    let title_height = title_font_size * 2.0;
+    let title_color = if svg_options.dark_mode {
+        "#FFFFFF"
+    } else {
+        "#000000"
+    };
+
+    let mut body = String::new();
+    let (mut width, mut height) = (0.0, 0.0f32);
+    for (title, dot_output) in panels {
+        let svg = render_svg(dot_output, graph, svg_options)?;
+        let start = svg.find("<svg").context("failed to find svg start")?;
+        let svg = svg[start..].trim_end();
+        let panel_width = svg_length(svg, "width")?;
+        height = height.max(svg_length(svg, "height")?);
+
+        body.push_str(&format!(
+            r#"<text x="{}pt" y="{title_font_size}pt" font-family="monospace" font-size="{title_font_size}pt" text-anchor="middle" fill="{title_color}">{}</text>
+"#,
+            width + panel_width / 2.0,
+            escape_xml(title)
+        ));
+        body.push_str(&svg.replacen(
+            "<svg",
+            &format!(r#"<svg x="{width}pt" y="{title_height}pt""#),
+            1,
+        ));
+        body.push('\n');
+        width += panel_width;
+    }
+
+    let background = if svg_options.dark_mode {
+        "<rect width=\"100%\" height=\"100%\" fill=\"#000000\"/>\n"
+    } else {
+        ""
+    };
+    let svg = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<svg width="{width}pt" height="{}pt" xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+{background}{body}</svg>
+"#,
+        height + title_height
+    );
+
+    write_svg(&svg, output_filename, svg_options)
+}
+
+/// Get the length in points of the given attribute of the first element of the SVG.
+fn svg_length(svg: &str, attribute: &str) -> anyhow::Result<f32> {
+    let pattern = format!("{attribute}=\"");
+    let start = svg
+        .find(&pattern)
+        .with_context(|| format!("failed to find svg {attribute}"))?
+        + pattern.len();
+    let end = svg[start..]
+        .find('"')
+        .with_context(|| format!("failed to find svg {attribute}"))?
+        + start;
+    svg[start..end]
+        .trim_end_matches("pt")
+        .parse()
+        .with_context(|| format!("failed to parse svg {attribute}"))
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Get the default edge width of the SVG output of the given graph.
+pub fn edge_width(graph: &Graph, scale_factor: Option<f32>) -> f32 {
+    arrow_size(graph, scale_factor) * 2.0
+}
+
+fn arrow_size(graph: &Graph, scale_factor: Option<f32>) -> f32 {
+    let node_count_factor = (graph.node_count() as f32 / 32.0).floor();
+    (node_count_factor * 0.2 + 0.6) * scale_factor.unwrap_or(1.0)
+}
+
+fn render_svg(dot_output: &str, graph: &Graph, svg_options: &SvgOptions) -> anyhow::Result<String> {
     let node_count_factor = (graph.node_count() as f32 / 32.0).floor();
     let scale_factor = svg_options.scale_factor.unwrap_or(1.0);
     let node_font_size = (node_count_factor * 3.0 + 15.0) * scale_factor;
     let edge_font_size = node_font_size * 0.75;
-    let arrow_size = (node_count_factor * 0.2 + 0.6) * scale_factor;
-    let edge_width = arrow_size * 2.0;
+    let arrow_size = arrow_size(graph, svg_options.scale_factor);
+    let edge_width = edge_width(graph, svg_options.scale_factor);
     let node_border_width = edge_width * 0.75;
 
     let sep_factor = svg_options.separation_factor.unwrap_or(1.0);
@@ -126,6 +249,7 @@ pub fn output_svg(
         .stdout(Stdio::piped())
         .arg("-Tsvg")
         .arg(format!("-Gpad={padding}"))
+        .arg("-Gfontname=monospace")
         .arg("-Nshape=circle")
         .arg(format!("-Npenwidth={node_border_width}"))
         .arg("-Nstyle=filled")
@@ -143,6 +267,8 @@ pub fn output_svg(
     if svg_options.dark_mode {
         command
             .arg("-Gbgcolor=#000000")
+            .arg("-Gcolor=#FFFFFF")
+            .arg("-Gfontcolor=#FFFFFF")
             .arg("-Ncolor=#FFFFFF")
             .arg("-Ecolor=#FFFFFF9F")
             .arg("-Efontcolor=#FFFFFFFF")
@@ -186,6 +312,10 @@ pub fn output_svg(
         svg.insert_str(index, &style);
     }
 
+    Ok(svg)
+}
+
+fn write_svg(svg: &str, output_filename: &str, svg_options: &SvgOptions) -> anyhow::Result<()> {
     std::fs::write(output_filename, svg).context("failed to write output svg file")?;
     if !svg_options.no_open {
         open::that_detached(output_filename).context("failed to open output svg")?;
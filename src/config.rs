@@ -4,7 +4,7 @@ use clap::Args;
 #[cfg(feature = "config")]
 use serde::de;
 
-use pugio_lib::coloring::{NodeColoringGradient, NodeColoringScheme};
+use pugio_lib::coloring::{Attribution, NodeColoringGradient, NodeColoringScheme};
 
 // Obfuscate type for clap
 type OptScheme = Option<NodeColoringScheme>;
@@ -16,6 +16,14 @@ type OptScheme = Option<NodeColoringScheme>;
 )]
 #[derive(Args)]
 pub struct Config {
+    /// Path to Cargo.toml
+    #[arg(long, value_name = "PATH")]
+    pub manifest_path: Option<String>,
+
+    /// JSON graph file path to read instead of running cargo, "-" for stdin
+    #[arg(long, value_name = "FILE")]
+    pub from_json: Option<String>,
+
     /// Package to inspect
     #[arg(short, long)]
     pub package: Option<String>,
@@ -43,6 +51,16 @@ pub struct Config {
     #[cfg_attr(feature = "config", serde(default))]
     pub release: bool,
 
+    /// Include only dependency names matching the regex patterns, and the paths to them
+    #[cfg(feature = "regex")]
+    #[arg(short = 'I', long)]
+    pub include: Option<Vec<String>>,
+
+    /// Include only dependency names matching the prefixes, and the paths to them
+    #[cfg(not(feature = "regex"))]
+    #[arg(short = 'I', long)]
+    pub include: Option<Vec<String>>,
+
     /// Exclude dependency names matching the regex patterns
     #[cfg(feature = "regex")]
     #[arg(short = 'E', long)]
@@ -53,6 +71,28 @@ pub struct Config {
     #[arg(short = 'E', long)]
     pub excludes: Option<Vec<String>>,
 
+    /// Hide dependency names matching the regex patterns, keeping their dependencies
+    #[cfg(feature = "regex")]
+    #[arg(short = 'H', long)]
+    pub hide: Option<Vec<String>>,
+
+    /// Hide dependency names matching the prefixes, keeping their dependencies
+    #[cfg(not(feature = "regex"))]
+    #[arg(short = 'H', long)]
+    pub hide: Option<Vec<String>>,
+
+    /// Group dependency names matching the regex pattern into one node if any, as "NAME=PATTERN"
+    #[cfg(feature = "regex")]
+    #[arg(long = "group", value_name = "NAME=PATTERN", value_parser = parse_group)]
+    #[cfg_attr(feature = "config", serde(deserialize_with = "de_groups", default))]
+    pub groups: Option<Vec<(String, String)>>,
+
+    /// Group dependency names matching the prefix into one node if any, as "NAME=PREFIX"
+    #[cfg(not(feature = "regex"))]
+    #[arg(long = "group", value_name = "NAME=PREFIX", value_parser = parse_group)]
+    #[cfg_attr(feature = "config", serde(deserialize_with = "de_groups", default))]
+    pub groups: Option<Vec<(String, String)>>,
+
     /// Change root to the unique dependency name matching the regex pattern
     #[cfg(feature = "regex")]
     #[arg(short = 'R', long)]
@@ -63,6 +103,26 @@ pub struct Config {
     #[arg(short = 'R', long)]
     pub root: Option<String>,
 
+    /// Focus on dependency names matching the regex pattern, and their neighbourhood
+    #[cfg(feature = "regex")]
+    #[arg(long)]
+    pub focus: Option<String>,
+
+    /// Focus on dependency names matching the prefix, and their neighbourhood
+    #[cfg(not(feature = "regex"))]
+    #[arg(long)]
+    pub focus: Option<String>,
+
+    /// Number of hops upwards (dependents) to keep in focus
+    ///  default: 1
+    #[arg(long, value_name = "HOPS", requires = "focus", verbatim_doc_comment)]
+    pub up: Option<usize>,
+
+    /// Number of hops downwards (dependencies) to keep in focus
+    ///  default: 1
+    #[arg(long, value_name = "HOPS", requires = "focus", verbatim_doc_comment)]
+    pub down: Option<usize>,
+
     /// Add std standalone node
     #[arg(long)]
     #[cfg_attr(feature = "config", serde(default))]
@@ -71,7 +131,13 @@ pub struct Config {
     /// Color scheme of nodes
     ///  - "cum-sum": cumulative sum of the size of a node and its dependencies (default)
     ///  - "dep-count": dependency count; number of transitive dependencies from a node
-    ///  - "rev-dep-count": reverse dependency count; number of paths from the root to a node
+    ///  - "rev-dep-count": reverse dependency count; number of direct dependents of a node
+    ///  - "path-count": number of paths from the root to a node
+    ///  - "exclusive": exclusive size; size that would be removed along with a node
+    ///  - "self-size": size of a node itself
+    ///  - "depth": minimum number of hops from the root to a node
+    ///  - "feature-count": number of enabled features of a node
+    ///  - "version-count": number of versions of the crate of a node
     ///  - "none"
     #[cfg_attr(
         feature = "config",
@@ -92,17 +158,48 @@ pub struct Config {
     #[arg(long, verbatim_doc_comment)]
     pub gamma: Option<f64>,
 
+    /// Attribution of the size of a dependency to its dependents in cumulative sums
+    ///  - "full": every dependent counts the whole size
+    ///  - "equal-split": split equally among direct dependents (default)
+    ///  - "proportional": split proportionally to the sizes of direct dependents
+    #[arg(long, verbatim_doc_comment)]
+    pub attribution: Option<Attribution>,
+
     /// Remove nodes that have cumulative sum below threshold
     ///  - human readable byte format, e.g. "21KiB", "69 KB"
+    ///  - percentage of the total size, e.g. "0.5%"
     ///  - "non-zero"
     #[arg(short, long, value_parser = parse_threshold, verbatim_doc_comment)]
     #[cfg_attr(feature = "config", serde(deserialize_with = "de_threshold", default))]
-    pub threshold: Option<usize>,
+    pub threshold: Option<Threshold>,
+
+    /// Keep only the top N nodes by size, and the paths to them
+    #[arg(long, value_name = "N")]
+    pub top: Option<usize>,
+
+    /// Basis of the threshold and top N
+    ///  - "cum-sum": cumulative sum (default)
+    ///  - "exclusive": exclusive size
+    #[arg(long, value_parser = parse_threshold_basis, verbatim_doc_comment)]
+    #[cfg_attr(
+        feature = "config",
+        serde(deserialize_with = "de_threshold_basis", default)
+    )]
+    pub threshold_basis: Option<NodeColoringScheme>,
 
     /// Remove nodes that are more than max depth deep
     #[arg(short = 'd', long = "depth", value_name = "MAX_DEPTH")]
     pub depth: Option<usize>,
 
+    /// Remove nodes matching the filter expression, after all other filters
+    ///  e.g. "cum_sum < 10KiB && depth > 2 && !workspace"
+    ///  - numbers: "size", "cum_sum", "exclusive", "dep_count", "rev_dep_count", "path_count",
+    ///    "depth", "feature_count"
+    ///  - strings: "name", "full", "version", "source", "features"
+    ///  - booleans: "workspace", "path", "git", "registry", "root", "std"
+    #[arg(long, value_name = "EXPR", verbatim_doc_comment)]
+    pub filter: Option<String>,
+
     /// Inverse color gradient
     #[arg(long)]
     #[cfg_attr(feature = "config", serde(default))]
@@ -113,6 +210,27 @@ pub struct Config {
     #[cfg_attr(feature = "config", serde(default))]
     pub dark_mode: bool,
 
+    /// Draw clusters around crates of the same source kind
+    ///  i.e. "workspace", "path", "git" and "registry"
+    #[arg(long, verbatim_doc_comment)]
+    #[cfg_attr(feature = "config", serde(default))]
+    pub cluster: bool,
+
+    /// Transitive reduction of edges, i.e. edges to dependencies also reachable otherwise
+    ///  - "hide": hide redundant edges
+    ///  - "faint": draw redundant edges faintly
+    #[arg(long, value_name = "MODE", value_parser = parse_transitive_reduction, verbatim_doc_comment)]
+    #[cfg_attr(
+        feature = "config",
+        serde(deserialize_with = "de_transitive_reduction", default)
+    )]
+    pub transitive_reduction: Option<bool>,
+
+    /// Mark crates with more than one version with a double border
+    #[arg(long)]
+    #[cfg_attr(feature = "config", serde(default))]
+    pub mark_duplicates: bool,
+
     /// Padding for output svg file
     ///  default: 1.0
     #[arg(long)]
@@ -134,6 +252,16 @@ pub struct Config {
     #[cfg_attr(feature = "config", serde(deserialize_with = "de_highlight", default))]
     pub highlight: Option<bool>,
 
+    /// Highlight a path from the root by drawing its edges in bold
+    ///  - "heaviest": path with the largest sum of sizes
+    ///  - "longest": path with the most dependencies
+    #[arg(long, value_name = "PATH", value_parser = parse_highlight_path, verbatim_doc_comment)]
+    #[cfg_attr(
+        feature = "config",
+        serde(deserialize_with = "de_highlight_path", default)
+    )]
+    pub highlight_path: Option<bool>,
+
     /// Highlight amount for output svg file, between 0.0 and 1.0
     ///  default: 0.5
     #[arg(long, verbatim_doc_comment)]
@@ -145,7 +273,7 @@ pub struct Config {
     pub node_label_template: Option<String>,
 
     /// Custom node tooltip formatting template
-    ///  default: "{full}\n{size_binary}\n{features}"
+    ///  default: "{full}\n{size_binary}\n{features}{{ if members }}\n{members}{{ endif }}"
     #[arg(long, verbatim_doc_comment)]
     pub node_tooltip_template: Option<String>,
 
@@ -164,6 +292,12 @@ pub struct Config {
     #[cfg_attr(feature = "config", serde(default))]
     pub dot_only: bool,
 
+    /// Output file format
+    ///  - "svg" (default), "dot", "json"
+    #[arg(long, value_parser = parse_format, verbatim_doc_comment)]
+    #[cfg_attr(feature = "config", serde(deserialize_with = "de_format", default))]
+    pub format: Option<Format>,
+
     /// Output filename, default is output.*
     #[arg(short, long)]
     pub output: Option<String>,
@@ -174,6 +308,20 @@ pub struct Config {
     pub no_open: bool,
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Svg,
+    Dot,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Threshold {
+    Bytes(usize),
+    Percent(f64),
+}
+
 #[cfg(feature = "config")]
 fn default_opt_scheme() -> OptScheme {
     Some(NodeColoringScheme::CumSum)
@@ -194,7 +342,23 @@ fn de_highlight<'de, D: de::Deserializer<'de>>(d: D) -> Result<Option<bool>, D::
 }
 
 #[cfg(feature = "config")]
-fn de_threshold<'de, D: de::Deserializer<'de>>(d: D) -> Result<Option<usize>, D::Error> {
+fn de_highlight_path<'de, D: de::Deserializer<'de>>(d: D) -> Result<Option<bool>, D::Error> {
+    let str: String = de::Deserialize::deserialize(d)?;
+    parse_highlight_path(&str)
+        .map(Option::Some)
+        .map_err(de::Error::custom)
+}
+
+#[cfg(feature = "config")]
+fn de_transitive_reduction<'de, D: de::Deserializer<'de>>(d: D) -> Result<Option<bool>, D::Error> {
+    let str: String = de::Deserialize::deserialize(d)?;
+    parse_transitive_reduction(&str)
+        .map(Option::Some)
+        .map_err(de::Error::custom)
+}
+
+#[cfg(feature = "config")]
+fn de_threshold<'de, D: de::Deserializer<'de>>(d: D) -> Result<Option<Threshold>, D::Error> {
     #[derive(serde::Deserialize)]
     #[serde(untagged)]
     enum Threshold {
@@ -204,13 +368,57 @@ fn de_threshold<'de, D: de::Deserializer<'de>>(d: D) -> Result<Option<usize>, D:
 
     let threshold: Threshold = de::Deserialize::deserialize(d)?;
     match threshold {
-        Threshold::Usize(u) => Ok(Some(u)),
+        Threshold::Usize(u) => Ok(Some(self::Threshold::Bytes(u))),
         Threshold::String(s) => parse_threshold(&s)
             .map(Option::Some)
             .map_err(de::Error::custom),
     }
 }
 
+#[cfg(feature = "config")]
+fn de_threshold_basis<'de, D: de::Deserializer<'de>>(
+    d: D,
+) -> Result<Option<NodeColoringScheme>, D::Error> {
+    let str: String = de::Deserialize::deserialize(d)?;
+    parse_threshold_basis(&str)
+        .map(Option::Some)
+        .map_err(de::Error::custom)
+}
+
+/// Deserialize groups from a table, keeping the order in which they are written.
+#[cfg(feature = "config")]
+fn de_groups<'de, D: de::Deserializer<'de>>(
+    d: D,
+) -> Result<Option<Vec<(String, String)>>, D::Error> {
+    struct GroupsVisitor;
+
+    impl<'de> de::Visitor<'de> for GroupsVisitor {
+        type Value = Vec<(String, String)>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a table of group names to patterns")
+        }
+
+        fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut groups = Vec::new();
+            while let Some(group) = map.next_entry()? {
+                groups.push(group);
+            }
+            Ok(groups)
+        }
+    }
+
+    d.deserialize_map(GroupsVisitor).map(Option::Some)
+}
+
+#[cfg(feature = "config")]
+fn de_format<'de, D: de::Deserializer<'de>>(d: D) -> Result<Option<Format>, D::Error> {
+    let str: String = de::Deserialize::deserialize(d)?;
+    parse_format(&str)
+        .map(Option::Some)
+        .map_err(de::Error::custom)
+}
+
 fn parse_scheme(
     s: &str,
 ) -> Result<Option<NodeColoringScheme>, <NodeColoringScheme as FromStr>::Err> {
@@ -220,6 +428,29 @@ fn parse_scheme(
     }
 }
 
+fn parse_threshold_basis(b: &str) -> Result<NodeColoringScheme, &'static str> {
+    match b {
+        "cum-sum" => Ok(NodeColoringScheme::CumSum),
+        "exclusive" => Ok(NodeColoringScheme::Exclusive),
+        _ => Err("invalid threshold basis value"),
+    }
+}
+
+fn parse_group(g: &str) -> Result<(String, String), &'static str> {
+    g.split_once('=')
+        .map(|(name, pattern)| (name.to_string(), pattern.to_string()))
+        .ok_or("invalid group value, expected NAME=PATTERN")
+}
+
+fn parse_format(f: &str) -> Result<Format, &'static str> {
+    match f {
+        "svg" => Ok(Format::Svg),
+        "dot" => Ok(Format::Dot),
+        "json" => Ok(Format::Json),
+        _ => Err("invalid format value"),
+    }
+}
+
 fn parse_highlight(h: &str) -> Result<bool, &'static str> {
     match h {
         "dep" => Ok(true),
@@ -228,10 +459,33 @@ fn parse_highlight(h: &str) -> Result<bool, &'static str> {
     }
 }
 
-fn parse_threshold(t: &str) -> Result<usize, parse_size::Error> {
+fn parse_highlight_path(p: &str) -> Result<bool, &'static str> {
+    match p {
+        "heaviest" => Ok(true),
+        "longest" => Ok(false),
+        _ => Err("invalid highlight path value"),
+    }
+}
+
+fn parse_transitive_reduction(r: &str) -> Result<bool, &'static str> {
+    match r {
+        "hide" => Ok(true),
+        "faint" => Ok(false),
+        _ => Err("invalid transitive reduction value"),
+    }
+}
+
+fn parse_threshold(t: &str) -> Result<Threshold, String> {
     if t == "non-zero" {
-        Ok(1)
+        Ok(Threshold::Bytes(1))
+    } else if let Some(p) = t.strip_suffix('%') {
+        match p.trim().parse::<f64>() {
+            Ok(p) if (0.0..=100.0).contains(&p) => Ok(Threshold::Percent(p)),
+            _ => Err("invalid percentage".to_string()),
+        }
     } else {
-        parse_size::parse_size(t).map(|b| b as usize)
+        parse_size::parse_size(t)
+            .map(|b| Threshold::Bytes(b as usize))
+            .map_err(|e| e.to_string())
     }
 }
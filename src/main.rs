@@ -1,18 +1,27 @@
 mod command;
 mod config;
+mod report;
 
-use command::{cargo_bloat_output, cargo_tree_output};
+use std::collections::HashMap;
+
+use command::{cargo_bloat_output, cargo_metadata_output, cargo_tree_output};
 
 use anyhow::{Context, bail};
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use pugio_lib::{
-    coloring::{NodeColoringScheme, NodeColoringValues},
+    coloring::{
+        NodeColoringCategoricalGradient, NodeColoringDivergingGradient, NodeColoringScheme,
+        NodeColoringValues,
+    },
+    diff::{DeltaValues, GraphDiff},
+    filter::Filter,
     graph::{DotOptions, Graph},
     template::{Template, TemplateOptions},
 };
 
-use crate::command::{CargoOptions, SvgOptions, output_svg};
-use crate::config::Config;
+use crate::command::{CargoOptions, SvgOptions, edge_width, output_panels_svg, output_svg};
+use crate::config::{Config, Format, Threshold};
+use crate::report::{print_diff, print_duplicates, print_paths, print_union};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
@@ -25,6 +34,71 @@ struct Args {
 
     #[command(flatten)]
     config: Config,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print dependency paths from the root to dependencies matching the pattern
+    Why {
+        /// Dependency name regex pattern
+        #[cfg(feature = "regex")]
+        pattern: String,
+
+        /// Dependency name prefix
+        #[cfg(not(feature = "regex"))]
+        pattern: String,
+
+        /// Maximum number of paths to print for each matched dependency
+        #[arg(long, default_value_t = 32)]
+        max_paths: usize,
+
+        /// Also output the graph restricted to the printed paths
+        #[arg(long)]
+        graph: bool,
+    },
+    /// Print and output the difference from another version of the package
+    Diff {
+        /// Path to Cargo.toml of the old version, e.g. in another git worktree,
+        /// or its JSON graph file
+        old: String,
+
+        /// Diverging color gradient of nodes by size delta
+        ///  - "rd-bu" (default), "rd-gy", "rd-yl-bu", "rd-yl-gn"
+        ///  - "pi-yg", "pr-gn", "br-bg", "pu-or", "spectral"
+        #[arg(short, long, verbatim_doc_comment)]
+        gradient: Option<NodeColoringDivergingGradient>,
+    },
+    /// Print crates with more than one version, their dependents and combined sizes
+    Duplicates,
+    /// Print and output the union of multiple binaries, colored by membership
+    Union {
+        /// Binaries to merge
+        #[arg(long, num_args = 2.., required = true)]
+        bins: Vec<String>,
+
+        /// Categorical color gradient of nodes by membership
+        ///  - "tableau10" (default), "category10", "set1", "set2", "dark2", "pastel1"
+        #[arg(short, long, verbatim_doc_comment)]
+        gradient: Option<NodeColoringCategoricalGradient>,
+    },
+    /// Output multiple binaries or feature sets side by side with shared layout and color scale
+    Panels {
+        /// Binaries of the panels
+        #[arg(
+            long,
+            num_args = 1..,
+            required_unless_present = "feature_sets",
+            conflicts_with = "feature_sets"
+        )]
+        bins: Vec<String>,
+
+        /// Feature sets of the panels, each a comma-separated list of features
+        #[arg(long, num_args = 1..)]
+        feature_sets: Vec<String>,
+    },
 }
 
 fn get_matched_node_indices(graph: &Graph, pattern: &str) -> anyhow::Result<Vec<usize>> {
@@ -65,27 +139,255 @@ fn main() -> anyhow::Result<()> {
         }
     }
 
-    let options = CargoOptions {
+    // Only built for the subcommands analysing the package as specified
+    let default_graph = || {
+        build_graph(
+            &config,
+            &cargo_options(&config),
+            config.from_json.as_deref(),
+        )
+    };
+
+    match args.command {
+        None => {
+            let mut graph = default_graph()?;
+            let node_values = node_values(&graph, &config);
+            filter(&mut graph, &config)?;
+            output(&graph, &node_values, config)
+        }
+        Some(Command::Why {
+            pattern,
+            max_paths,
+            graph: is_output,
+        }) => {
+            let graph = default_graph()?;
+            let indices = get_matched_node_indices(&graph, &pattern)?;
+            if indices.is_empty() {
+                bail!("dependency name pattern not found");
+            }
+
+            let mut is_on_path = vec![false; graph.node_capacity()];
+            for index in indices {
+                let (paths, count) = graph.paths_to(index, max_paths);
+                print_paths(&graph, index, &paths, count);
+                for path in paths {
+                    for node in path {
+                        is_on_path[node] = true;
+                    }
+                }
+            }
+
+            if is_output {
+                let node_values = node_values(&graph, &config);
+                let graph = graph.subgraph(graph.node_indices().filter(|i| is_on_path[*i]));
+                output(&graph, &node_values, config)?;
+            }
+
+            Ok(())
+        }
+        Some(Command::Diff { old, gradient }) => {
+            reject_filters(&config, "diff")?;
+            let graph = default_graph()?;
+            let old_graph = if old.ends_with(".json") {
+                build_graph(&config, &cargo_options(&config), Some(&old))?
+            } else {
+                let options = CargoOptions {
+                    manifest_path: Some(old),
+                    ..cargo_options(&config)
+                };
+                build_graph(&config, &options, None)?
+            };
+
+            let diff = GraphDiff::new(&old_graph, &graph);
+            print_diff(&diff);
+
+            let mut values = DeltaValues::new(&diff);
+            if let Some(gamma) = config.gamma {
+                values.set_gamma(gamma);
+            }
+
+            let gradient = gradient.unwrap_or_default();
+            let dot_options = dot_options(diff.graph(), &config);
+            let dot = || diff.output_dot(&dot_options, &values, &gradient);
+            write_output(diff.graph(), dot, config)
+        }
+        Some(Command::Duplicates) => {
+            print_duplicates(&default_graph()?);
+            Ok(())
+        }
+        Some(Command::Union { bins, gradient }) => {
+            if config.from_json.is_some() {
+                bail!("--from-json is not supported by the union subcommand");
+            }
+            reject_filters(&config, "union")?;
+
+            let graphs = bins
+                .iter()
+                .map(|bin| {
+                    let options = CargoOptions {
+                        bin: Some(bin.clone()),
+                        ..cargo_options(&config)
+                    };
+                    build_graph(&config, &options, None)
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+
+            let union = Graph::union(&graphs);
+            print_union(&union, &bins);
+
+            let gradient = gradient.unwrap_or_default();
+            let dot_options = dot_options(union.graph(), &config);
+            let dot = || {
+                union
+                    .graph()
+                    .output_dot(&dot_options, &union, &union, &gradient)
+            };
+            write_output(union.graph(), dot, config)
+        }
+        Some(Command::Panels { bins, feature_sets }) => {
+            if config.from_json.is_some() {
+                bail!("--from-json is not supported by the panels subcommand");
+            }
+            reject_filters(&config, "panels")?;
+
+            let (titles, options): (Vec<_>, Vec<_>) = if bins.is_empty() {
+                feature_sets
+                    .into_iter()
+                    .map(|features| {
+                        let options = CargoOptions {
+                            features: Some(features.clone()),
+                            ..cargo_options(&config)
+                        };
+                        (features, options)
+                    })
+                    .unzip()
+            } else {
+                bins.into_iter()
+                    .map(|bin| {
+                        let options = CargoOptions {
+                            bin: Some(bin.clone()),
+                            ..cargo_options(&config)
+                        };
+                        (bin, options)
+                    })
+                    .unzip()
+            };
+
+            let graphs = options
+                .iter()
+                .map(|options| build_graph(&config, options, None))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let union = Graph::union(&graphs);
+
+            let panels = (0..titles.len())
+                .map(|i| union.panel(i))
+                .collect::<Vec<_>>();
+            let mut panel_values = panels
+                .iter()
+                .enumerate()
+                .map(|(i, panel)| {
+                    let root = panel.root();
+                    let graph = panel.view(|g| {
+                        let indices = union
+                            .graph()
+                            .node_indices()
+                            .filter(|j| j != &root && union.sizes(*j)[i].is_none());
+                        g.remove_indices(indices);
+                    });
+                    node_values(&graph, &config)
+                })
+                .collect::<Vec<_>>();
+            let max = panel_values.iter().flatten().map(|v| v.max()).max();
+            for values in panel_values.iter_mut().flatten() {
+                values.set_max(max.unwrap_or_default());
+            }
+
+            let template_options = template_options(&config);
+            let template = Template::new(&template_options).context("failed to parse templates")?;
+            let gradient = config.gradient.unwrap_or_default();
+            let dots = panels
+                .iter()
+                .zip(panel_values)
+                .enumerate()
+                .map(|(i, (panel, values))| {
+                    let mut dot_options = dot_options(panel, &config);
+                    merge_attributes(
+                        &mut dot_options.node_attributes,
+                        union.panel_node_attributes(i),
+                    );
+                    merge_attributes(
+                        &mut dot_options.edge_attributes,
+                        union.panel_edge_attributes(i),
+                    );
+                    panel.output_dot(&dot_options, &template, &values, &gradient)
+                })
+                .collect::<Vec<_>>();
+
+            write_panels_output(
+                union.graph(),
+                titles.into_iter().zip(dots).collect(),
+                config,
+            )
+        }
+    }
+}
+
+/// Append the given attributes to the existing ones.
+fn merge_attributes<K: std::hash::Hash + Eq>(
+    attributes: &mut HashMap<K, String>,
+    other: HashMap<K, String>,
+) {
+    for (key, value) in other {
+        attributes
+            .entry(key)
+            .and_modify(|a| {
+                a.push(' ');
+                a.push_str(&value);
+            })
+            .or_insert(value);
+    }
+}
+
+fn cargo_options(config: &Config) -> CargoOptions {
+    CargoOptions {
+        manifest_path: config.manifest_path.clone(),
         package: config.package.clone(),
         bin: config.bin.clone(),
         features: config.features.clone(),
         all_features: config.all_features,
         no_default_features: config.no_default_features,
         release: config.release,
-    };
-
-    let cargo_tree_output = cargo_tree_output(&options)?;
-    if cargo_tree_output.contains("\n\n") || cargo_tree_output.contains("\r\n\r\n") {
-        bail!("one and only one package must be specified");
     }
-    let cargo_bloat_output = cargo_bloat_output(&options)?;
+}
 
-    let mut graph = Graph::new(
-        &cargo_tree_output,
-        &cargo_bloat_output,
-        config.std,
-        config.bin.as_deref(),
-    );
+fn build_graph(
+    config: &Config,
+    options: &CargoOptions,
+    json_file: Option<&str>,
+) -> anyhow::Result<Graph> {
+    let mut graph = if let Some(json_file) = json_file {
+        let json = if json_file == "-" {
+            std::io::read_to_string(std::io::stdin()).context("failed to read from stdin")?
+        } else {
+            std::fs::read_to_string(json_file).context("failed to read json file")?
+        };
+        serde_json::from_str(&json).context("failed to parse json file")?
+    } else {
+        let cargo_tree_output = cargo_tree_output(options)?;
+        if cargo_tree_output.contains("\n\n") || cargo_tree_output.contains("\r\n\r\n") {
+            bail!("one and only one package must be specified");
+        }
+        let cargo_bloat_output = cargo_bloat_output(options)?;
+        let cargo_metadata_output = cargo_metadata_output(options)?;
+
+        Graph::new(
+            &cargo_tree_output,
+            &cargo_bloat_output,
+            &cargo_metadata_output,
+            config.std,
+            options.bin.as_deref(),
+        )
+    };
 
     if let Some(root) = &config.root {
         let indices = get_matched_node_indices(&graph, root)?;
@@ -98,83 +400,273 @@ fn main() -> anyhow::Result<()> {
         }
     }
 
-    let gradient = config.gradient.unwrap_or_default();
-    let node_values = match config.scheme {
-        None => None,
-        Some(scheme) => {
-            let mut node_values = NodeColoringValues::new(&graph, scheme);
-
-            if let Some(gamma) = config.gamma {
-                node_values.set_gamma(gamma);
+    if let Some(groups) = &config.groups {
+        for (name, pattern) in groups {
+            let indices = get_matched_node_indices(&graph, pattern)?;
+            // A shared group may not match anything on every target
+            if indices.is_empty() {
+                continue;
             }
+            graph
+                .group(name, indices.into_iter())
+                .context("failed to group dependencies")?;
+        }
+    }
 
-            Some(node_values)
+    if let Some(focus) = &config.focus {
+        let indices = get_matched_node_indices(&graph, focus)?;
+        if indices.is_empty() {
+            bail!("dependency name pattern not found");
         }
-    };
+        graph.focus(
+            indices.into_iter(),
+            config.up.unwrap_or(1),
+            config.down.unwrap_or(1),
+        );
+    }
+
+    Ok(graph)
+}
+
+fn node_values(graph: &Graph, config: &Config) -> Option<NodeColoringValues> {
+    config.scheme.map(|scheme| {
+        let mut node_values =
+            NodeColoringValues::new(graph, scheme, config.attribution.unwrap_or_default());
 
+        if let Some(gamma) = config.gamma {
+            node_values.set_gamma(gamma);
+        }
+
+        node_values
+    })
+}
+
+fn filter(graph: &mut Graph, config: &Config) -> anyhow::Result<()> {
     if let Some(threshold) = config.threshold {
-        let cum_sums = NodeColoringValues::new(&graph, NodeColoringScheme::CumSum);
-        let std = graph.std();
+        let basis = match config.threshold_basis {
+            Some(NodeColoringScheme::Exclusive) => "exclusive",
+            _ => "cum_sum",
+        };
+        let threshold = match threshold {
+            Threshold::Bytes(b) => b.to_string(),
+            Threshold::Percent(p) => format!("{p}%"),
+        };
+        remove_matching(graph, &format!("{basis} < {threshold} && !std"), config)?;
+    }
 
-        let iter = cum_sums
-            .indices_values()
-            .filter(|(i, s)| *s < threshold && Some(*i) != std)
-            .map(|(i, _)| i);
+    if let Some(top) = config.top {
+        let basis = config.threshold_basis.unwrap_or(NodeColoringScheme::CumSum);
+        let values = NodeColoringValues::new(graph, basis, config.attribution.unwrap_or_default());
+        graph.retain_top(top, &values);
+    }
 
-        graph.remove_indices(iter);
+    if let Some(include) = &config.include {
+        let indices = include.iter().try_fold(Vec::new(), |mut v, e| {
+            let indices = get_matched_node_indices(graph, e)?;
+            v.extend(indices);
+            Ok::<_, anyhow::Error>(v)
+        })?;
+        graph.retain_paths_to(indices.into_iter());
+    }
+
+    if let Some(excludes) = &config.excludes
+        && !excludes.is_empty()
+    {
+        let expr = excludes
+            .iter()
+            .map(|e| format!("full =~ {}", quote(e)))
+            .collect::<Vec<_>>()
+            .join(" || ");
+        remove_matching(graph, &expr, config)?;
     }
 
-    if let Some(excludes) = &config.excludes {
-        let indices = excludes.iter().try_fold(Vec::new(), |mut v, e| {
-            let indices = get_matched_node_indices(&graph, e)?;
+    if let Some(hide) = &config.hide {
+        let indices = hide.iter().try_fold(Vec::new(), |mut v, e| {
+            let indices = get_matched_node_indices(graph, e)?;
             v.extend(indices);
             Ok::<_, anyhow::Error>(v)
         })?;
-        graph.remove_indices(indices.into_iter());
+        graph.contract_indices(indices.into_iter());
     }
 
     if let Some(depth) = config.depth {
-        graph.remove_deep_deps(depth);
+        remove_matching(graph, &format!("depth > {depth}"), config)?;
     }
 
-    let output_filename = config.output.as_deref();
+    if let Some(filter) = &config.filter {
+        remove_matching(graph, filter, config)?;
+    }
+
+    Ok(())
+}
+
+/// Reject the options of [`filter`] for subcommands whose graphs are not filtered, as they would
+/// otherwise be silently ignored.
+fn reject_filters(config: &Config, command: &str) -> anyhow::Result<()> {
+    let options = [
+        ("--threshold", config.threshold.is_some()),
+        ("--top", config.top.is_some()),
+        ("--include", config.include.is_some()),
+        (
+            "--excludes",
+            config.excludes.as_ref().is_some_and(|e| !e.is_empty()),
+        ),
+        ("--hide", config.hide.is_some()),
+        ("--depth", config.depth.is_some()),
+        ("--filter", config.filter.is_some()),
+    ];
 
-    let template_options = TemplateOptions {
-        node_label_template: config.node_label_template,
-        node_tooltip_template: config.node_tooltip_template,
-        edge_label_template: config.edge_label_template,
-        edge_tooltip_template: config.edge_tooltip_template,
+    if let Some((option, _)) = options.iter().find(|(_, is_set)| *is_set) {
+        bail!("{option} is not supported by the {command} subcommand");
+    }
+
+    Ok(())
+}
+
+fn remove_matching(graph: &mut Graph, expr: &str, config: &Config) -> anyhow::Result<()> {
+    let mut filter =
+        Filter::parse(expr).with_context(|| format!("failed to parse filter \"{expr}\""))?;
+    filter.set_attribution(config.attribution.unwrap_or_default());
+    let indices = filter.matched_indices(graph);
+    graph.remove_indices(indices.into_iter());
+    Ok(())
+}
+
+/// Quote the given string as a filter expression string literal.
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn dot_options(graph: &Graph, config: &Config) -> DotOptions {
+    let node_attributes = if config.mark_duplicates {
+        graph
+            .duplicates()
+            .into_values()
+            .flatten()
+            .map(|i| (i, "peripheries = 2".to_string()))
+            .collect()
+    } else {
+        HashMap::new()
     };
-    let template = Template::new(&template_options).context("failed to parse templates")?;
 
-    let dot_options = DotOptions {
+    let edge_attributes = if let Some(is_heaviest) = config.highlight_path {
+        let path = if is_heaviest {
+            graph.heaviest_path()
+        } else {
+            graph.longest_path()
+        };
+        let penwidth = edge_width(graph, config.scale_factor) * 2.0;
+        path.windows(2)
+            .map(|w| ((w[0], w[1]), format!("penwidth = {penwidth}")))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    DotOptions {
         highlight: config.highlight,
-        bin: config.bin,
+        bin: config.bin.clone(),
         inverse_gradient: config.inverse_gradient,
         dark_mode: config.dark_mode,
+        cluster: config.cluster,
+        transitive_reduction: config.transitive_reduction,
+        node_attributes,
+        edge_attributes,
+    }
+}
+
+fn output(
+    graph: &Graph,
+    node_values: &Option<NodeColoringValues>,
+    config: Config,
+) -> anyhow::Result<()> {
+    let template_options = template_options(&config);
+    let template = Template::new(&template_options).context("failed to parse templates")?;
+    let gradient = config.gradient.unwrap_or_default();
+
+    let dot_options = dot_options(graph, &config);
+    let dot = || graph.output_dot(&dot_options, &template, node_values, &gradient);
+    write_output(graph, dot, config)
+}
+
+fn template_options(config: &Config) -> TemplateOptions {
+    TemplateOptions {
+        node_label_template: config.node_label_template.clone(),
+        node_tooltip_template: config.node_tooltip_template.clone(),
+        edge_label_template: config.edge_label_template.clone(),
+        edge_tooltip_template: config.edge_tooltip_template.clone(),
+    }
+}
+
+fn write_output(graph: &Graph, dot: impl FnOnce() -> String, config: Config) -> anyhow::Result<()> {
+    let output_filename = config.output.as_deref();
+    let format = if config.dot_only {
+        Format::Dot
+    } else {
+        config.format.unwrap_or_default()
     };
 
-    let dot = graph.output_dot(&dot_options, &template, &node_values, &gradient);
+    if format == Format::Json {
+        let json = serde_json::to_string_pretty(graph).context("failed to serialize graph")?;
+        std::fs::write(output_filename.unwrap_or("output.json"), json)
+            .context("failed to write output json file")?;
+    } else if format == Format::Dot {
+        std::fs::write(output_filename.unwrap_or("output.gv"), dot())
+            .context("failed to write output dot file")?;
+    } else {
+        output_svg(
+            &dot(),
+            graph,
+            output_filename.unwrap_or("output.svg"),
+            &svg_options(&config),
+        )?;
+    }
+
+    Ok(())
+}
+
+fn svg_options(config: &Config) -> SvgOptions {
+    SvgOptions {
+        scale_factor: config.scale_factor,
+        separation_factor: config.separation_factor,
+        padding: config.padding,
+        dark_mode: config.dark_mode,
+        highlight: config.highlight,
+        highlight_amount: config.highlight_amount,
+        no_open: config.no_open,
+    }
+}
+
+fn write_panels_output(
+    graph: &Graph,
+    panels: Vec<(String, String)>,
+    config: Config,
+) -> anyhow::Result<()> {
+    let output_filename = config.output.as_deref();
+    let format = if config.dot_only {
+        Format::Dot
+    } else {
+        config.format.unwrap_or_default()
+    };
 
-    if config.dot_only {
+    if format == Format::Json {
+        let json = serde_json::to_string_pretty(graph).context("failed to serialize graph")?;
+        std::fs::write(output_filename.unwrap_or("output.json"), json)
+            .context("failed to write output json file")?;
+    } else if format == Format::Dot {
+        let dot = panels
+            .into_iter()
+            .map(|(_, dot)| dot)
+            .collect::<Vec<_>>()
+            .join("\n");
         std::fs::write(output_filename.unwrap_or("output.gv"), dot)
             .context("failed to write output dot file")?;
     } else {
-        let svg_options = SvgOptions {
-            scale_factor: config.scale_factor,
-            separation_factor: config.separation_factor,
-            padding: config.padding,
-            dark_mode: config.dark_mode,
-            highlight: config.highlight,
-            highlight_amount: config.highlight_amount,
-            no_open: config.no_open,
-        };
-
-        output_svg(
-            &dot,
-            &graph,
+        output_panels_svg(
+            &panels,
+            graph,
             output_filename.unwrap_or("output.svg"),
-            &svg_options,
+            &svg_options(&config),
         )?;
     }
 
@@ -0,0 +1,192 @@
+use pugio_lib::{
+    diff::{DiffKind, GraphDiff},
+    graph::Graph,
+    union::GraphUnion,
+};
+
+fn node_line(graph: &Graph, index: usize) -> String {
+    let size = graph.size(index).unwrap_or_default();
+    format!(
+        "{} ({})",
+        graph.node_weight(index).full(),
+        humansize::format_size(size, humansize::BINARY)
+    )
+}
+
+/// Print the dependency paths to the node at the given index as a tree from the root.
+pub fn print_paths(graph: &Graph, index: usize, paths: &[Vec<usize>], count: usize) {
+    struct Trie {
+        index: usize,
+        children: Vec<Trie>,
+    }
+
+    fn print(graph: &Graph, trie: &Trie, prefix: &str) {
+        for (i, child) in trie.children.iter().enumerate() {
+            let is_last = i == trie.children.len() - 1;
+            let (branch, indent) = if is_last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            println!("{prefix}{branch}{}", node_line(graph, child.index));
+            print(graph, child, &format!("{prefix}{indent}"));
+        }
+    }
+
+    let count = if count == usize::MAX {
+        format!("{count}+")
+    } else {
+        count.to_string()
+    };
+    println!(
+        "{}: {count} path(s), showing {}",
+        graph.node_weight(index).full(),
+        paths.len()
+    );
+
+    let Some(root) = paths.first().map(|p| p[0]) else {
+        println!();
+        return;
+    };
+
+    let mut trie = Trie {
+        index: root,
+        children: Vec::new(),
+    };
+
+    for path in paths {
+        let mut node = &mut trie;
+        for index in path.iter().skip(1) {
+            let position = match node.children.iter().position(|c| c.index == *index) {
+                Some(position) => position,
+                None => {
+                    node.children.push(Trie {
+                        index: *index,
+                        children: Vec::new(),
+                    });
+                    node.children.len() - 1
+                }
+            };
+            node = &mut node.children[position];
+        }
+    }
+
+    println!("{}", node_line(graph, trie.index));
+    print(graph, &trie, "");
+    println!();
+}
+
+/// Print the changed crates of the difference, sorted by the magnitude of their size deltas.
+pub fn print_diff(diff: &GraphDiff) {
+    let graph = diff.graph();
+
+    let mut changes = graph
+        .node_indices()
+        .map(|i| (i, diff.node_diff(i)))
+        .filter(|(_, d)| d.kind() != DiffKind::Unchanged)
+        .collect::<Vec<_>>();
+    changes.sort_by_key(|(_, d)| std::cmp::Reverse(d.delta().unsigned_abs()));
+
+    let mut total = 0;
+    for (index, node_diff) in changes {
+        let node = graph.node_weight(index);
+        let kind: &'static str = node_diff.kind().into();
+        let name = match diff.old_extra(index) {
+            Some(old_extra) => format!("{} {old_extra} -> {}", node.short(), node.extra()),
+            None => node.full().to_string(),
+        };
+        total += node_diff.delta();
+        println!("{kind:>9} {:>12} {name}", format_delta(node_diff.delta()));
+    }
+
+    println!("{:>9} {:>12}", "total", format_delta(total));
+}
+
+/// Print the crates with more than one version, sorted by their combined sizes, along with the
+/// dependents of each version.
+pub fn print_duplicates(graph: &Graph) {
+    let mut duplicates = graph
+        .duplicates()
+        .into_iter()
+        .map(|(name, indices)| {
+            let size: usize = indices
+                .iter()
+                .map(|i| graph.size(*i).unwrap_or_default())
+                .sum();
+            (name, indices, size)
+        })
+        .collect::<Vec<_>>();
+    duplicates.sort_by_key(|(_, _, size)| std::cmp::Reverse(*size));
+
+    if duplicates.is_empty() {
+        println!("no duplicate crates found");
+        return;
+    }
+
+    for (name, indices, size) in duplicates {
+        println!(
+            "{name}: {} versions, {} in total",
+            indices.len(),
+            humansize::format_size(size, humansize::BINARY)
+        );
+        for index in indices {
+            let dependents = graph
+                .neighbors(index, false)
+                .map(|i| graph.node_weight(i).full())
+                .collect::<Vec<_>>()
+                .join(", ");
+            println!("    {} <- {dependents}", node_line(graph, index));
+        }
+    }
+}
+
+/// Print the total and unique sizes of each source of the union, and the number of shared crates.
+pub fn print_union(union: &GraphUnion, names: &[String]) {
+    let graph = union.graph();
+    let format = |size: usize| humansize::format_size(size, humansize::BINARY);
+
+    for (source, name) in names.iter().enumerate() {
+        let (mut count, mut unique, mut total) = (0, 0, 0);
+        for index in graph.node_indices() {
+            let Some(size) = union.sizes(index)[source] else {
+                continue;
+            };
+            total += size;
+            if union.only_in(index) == Some(source) {
+                count += 1;
+                unique += size;
+            }
+        }
+        println!(
+            "#{source} {name}: {} in total, {} in {count} unique crate(s)",
+            format(total),
+            format(unique)
+        );
+    }
+
+    let shared = graph
+        .node_indices()
+        .filter(|i| union.sizes(*i).iter().flatten().count() > 1)
+        .collect::<Vec<_>>();
+    let size = shared
+        .iter()
+        .map(|i| {
+            union
+                .sizes(*i)
+                .iter()
+                .flatten()
+                .max()
+                .copied()
+                .unwrap_or_default()
+        })
+        .sum();
+    println!("shared: {} in {} crate(s)", format(size), shared.len());
+}
+
+fn format_delta(delta: isize) -> String {
+    let sign = if delta < 0 { "-" } else { "+" };
+    format!(
+        "{sign}{}",
+        humansize::format_size(delta.unsigned_abs(), humansize::BINARY)
+    )
+}